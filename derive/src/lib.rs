@@ -0,0 +1,211 @@
+#![warn(clippy::pedantic)]
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr, Path, Type};
+
+/**
+Derives `FromStr` for a struct with named fields from a format string, given by a
+`#[parse("...")]` attribute on the struct. Each `{field}` in the format captures the text up to the
+literal text that follows it, and `{{` and `}}` stand for literal braces. Every field must appear
+exactly once.
+
+Fields are parsed with their own `FromStr` after trimming whitespace, unless they have a
+`#[parse(with = path)]` attribute, in which case `path` is called with the captured text and must
+return `Result<T, ParseError>`.
+
+A struct with a lifetime parameter `'a` can borrow from the text instead: it gets an inherent
+`fn parse(s: &'a str) -> Result<Self, ParseError>` rather than `FromStr`, its `&'a str` fields are
+the trimmed captured text, and `with` parsers are given the captured text as a `&'a str`.
+
+The generated code refers to `crate::util`, so this can only be used inside the aoc2023 crate. The
+error type is `crate::util::ParseError`, naming the field that failed and the column it starts at.
+*/
+#[proc_macro_derive(Parse, attributes(parse))]
+pub fn derive_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "Parse can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            name,
+            "Parse can only be derived for structs with named fields",
+        ));
+    };
+
+    let format = struct_format(input)?;
+    let (literals, placeholders) =
+        split_format(&format.value()).map_err(|msg| Error::new(format.span(), msg))?;
+
+    let mut inits = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        let field_name = ident.to_string();
+        let positions: Vec<_> = placeholders
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| **p == field_name)
+            .map(|(i, _)| i)
+            .collect();
+        let i = match positions[..] {
+            [i] => i,
+            [] => {
+                return Err(Error::new(
+                    format.span(),
+                    format!("no {{{field_name}}} in the format"),
+                ))
+            }
+            _ => {
+                return Err(Error::new(
+                    format.span(),
+                    format!("{{{field_name}}} appears more than once in the format"),
+                ))
+            }
+        };
+
+        let ty = &field.ty;
+        let value = if let Some(with) = field_parser(field)? {
+            quote! {
+                crate::util::parse_field(fields[#i].0, || #with(fields[#i].1))
+            }
+        } else if is_str_ref(ty) {
+            quote! {
+                ::std::result::Result::<_, crate::util::ParseError>::Ok(fields[#i].1.trim())
+            }
+        } else {
+            quote! {
+                crate::util::parse_at::<#ty>(fields[#i].1, fields[#i].0)
+            }
+        };
+        inits.push(quote! {
+            #ident: #value.map_err(|e| e.for_field(#field_name))?
+        });
+    }
+    if let Some(unknown) = placeholders
+        .iter()
+        .find(|p| !fields.named.iter().any(|f| f.ident.as_ref().unwrap() == p))
+    {
+        return Err(Error::new(
+            format.span(),
+            format!("{name} has no field {unknown}"),
+        ));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let body = quote! {
+        let fields = crate::util::match_literals(&[#(#literals),*], s)?;
+        ::std::result::Result::Ok(#name {
+            #(#inits,)*
+        })
+    };
+    if let Some(lifetime) = input.generics.lifetimes().next() {
+        let lifetime = &lifetime.lifetime;
+        let vis = &input.vis;
+        Ok(quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #vis fn parse(s: &#lifetime str) -> ::std::result::Result<Self, crate::util::ParseError> {
+                    #body
+                }
+            }
+        })
+    } else {
+        Ok(quote! {
+            impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+                type Err = crate::util::ParseError;
+
+                fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                    #body
+                }
+            }
+        })
+    }
+}
+
+fn is_str_ref(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(r) if matches!(&*r.elem, Type::Path(p) if p.path.is_ident("str")))
+}
+
+fn struct_format(input: &DeriveInput) -> syn::Result<LitStr> {
+    let mut format = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("parse")) {
+        if format.is_some() {
+            return Err(Error::new_spanned(attr, "more than one format given"));
+        }
+        format = Some(attr.parse_args::<LitStr>()?);
+    }
+    format.ok_or_else(|| {
+        Error::new(
+            Span::call_site(),
+            "Parse needs a format, like #[parse(\"{a},{b}\")]",
+        )
+    })
+}
+
+fn field_parser(field: &syn::Field) -> syn::Result<Option<Path>> {
+    let mut with = None;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("parse")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("with") {
+                with = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `with = path`"))
+            }
+        })?;
+    }
+    Ok(with)
+}
+
+/**
+Splits a format into the literal text around the placeholders, which always has one more entry than
+the placeholders, and the placeholder names
+*/
+fn split_format(format: &str) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut literals = vec![String::new()];
+    let mut placeholders = Vec::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literals.last_mut().unwrap().push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literals.last_mut().unwrap().push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) if c == '_' || c.is_alphanumeric() => name.push(c),
+                        _ => return Err(format!("unterminated placeholder {{{name}")),
+                    }
+                }
+                if !placeholders.is_empty() && literals.last().unwrap().is_empty() {
+                    return Err(format!(
+                        "{{{name}}} directly follows another placeholder, so there is nothing to split them on"
+                    ));
+                }
+                placeholders.push(name);
+                literals.push(String::new());
+            }
+            '}' => return Err("unmatched }".to_string()),
+            c => literals.last_mut().unwrap().push(c),
+        }
+    }
+    Ok((literals, placeholders))
+}
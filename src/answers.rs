@@ -17,7 +17,7 @@ macro_rules! answer_tests {
                 $crate::init_test_logging();
                 let Some(expected) = read_answers($day, 0) else { return };
                 let input = $crate::read_input($day, "1");
-                let ans = $crate::[<day $day>]::Solution.solve_1(input);
+                let ans = $crate::[<day $day>]::Solution.solve_1(&input);
                 assert_eq!(expected, ans);
             }
 
@@ -26,7 +26,7 @@ macro_rules! answer_tests {
                 $crate::init_test_logging();
                 let Some(expected) = read_answers($day, 1) else { return };
                 let input = $crate::read_input($day, "2");
-                let ans = $crate::[<day $day>]::Solution.solve_2(input);
+                let ans = $crate::[<day $day>]::Solution.solve_2(&input);
                 assert_eq!(expected, ans);
             }
         }
@@ -5,13 +5,13 @@ use std::collections::HashMap;
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        process(&input, &Regex::new("\\d").unwrap())
+    fn solve_1(&self, input: &str) -> String {
+        process(input, &Regex::new("\\d").unwrap())
     }
 
-    fn solve_2(&self, input: String) -> String {
+    fn solve_2(&self, input: &str) -> String {
         process(
-            &input,
+            input,
             &Regex::new("\\d|one|two|three|four|five|six|seven|eight|nine").unwrap(),
         )
     }
@@ -79,7 +79,7 @@ xtwone3four
 zoneight234
 7pqrstsixteen"
             .to_string();
-        let res = Solution.solve_2(inp);
+        let res = Solution.solve_2(&inp);
         assert_eq!(res, "281");
     }
 }
@@ -1,79 +1,143 @@
-use crate::util::{Dir, Grid, Point};
+use crate::util::{interior_points, signed_double_area, Dir, Grid, Point};
 use std::collections::HashSet;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
+    fn solve_1(&self, input: &str) -> String {
         let grid = input.parse().unwrap();
-        let d = get_loop_points(&grid).0.len();
+        let d = get_loop(&grid).len();
         (d / 2).to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let mut grid: Grid<Pipe> = input.parse().unwrap();
-        let (loop_points, start_pos) = get_loop_points(&grid);
-        for p in grid.points() {
-            if !loop_points.contains(&p) {
-                grid[p] = Pipe::Ground;
-            }
-        }
-        let start_pipe = Pipe::NE; // TODO generalize
-        grid[start_pos] = start_pipe;
-
-        let mut lefts = HashSet::new();
-        let mut rights = HashSet::new();
-
-        let mut pos = start_pos;
-        let mut dir = Dir::E; // TODO generalize
-        loop {
-            log::debug!("{pos} {dir:?}");
-            let pipe = grid[pos];
-            let (left, right) = pipe.left_right(pos, dir);
-            flood(&grid, left, &mut lefts);
-            flood(&grid, right, &mut rights);
-            let (new_pos, new_dir) = pipe.go(pos, dir);
-            if new_pos == start_pos {
-                break;
-            }
-            pos = new_pos;
-            dir = new_dir;
+    fn solve_2(&self, input: &str) -> String {
+        let grid = input.parse().unwrap();
+        let tiles = get_loop(&grid);
+        if log::log_enabled!(log::Level::Debug) {
+            let inside = inside_tiles(&tiles);
+            log::debug!(
+                "{:?} loop:\n{}",
+                winding(&tiles),
+                render(&grid, &tiles, &inside)
+            );
         }
-
-        // wat - assumes the outside set will contain something on the left or top borders
-        let inside = if lefts.iter().any(|p| p.0 == 0 || p.1 == 0) {
-            rights
-        } else {
-            lefts
-        };
-        inside.len().to_string()
+        let vertices: Vec<Point<i64>> = tiles.into_iter().map(Point::into).collect();
+        // every tile of the loop is a lattice point on its boundary
+        interior_points(&vertices).to_string()
     }
 }
 
-fn get_loop_points(grid: &Grid<Pipe>) -> (HashSet<Point>, Point) {
+/**
+The tiles of the loop through the start, in order
+*/
+fn get_loop(grid: &Grid<Pipe>) -> Vec<Point> {
     let start_pos = grid
         .points_with_item()
         .find(|(_p, t)| **t == Pipe::Start)
         .unwrap()
         .0;
-    let start_pipe = Pipe::NE; // TODO generalize
-    let [mut this, last] = start_pipe.adj(start_pos);
-    let mut res = HashSet::new();
-    res.insert(start_pos);
-    while this != last {
-        //log::debug!("this = {this} d = {}", res.len());
-        let pipe = grid[this];
-        for a in pipe.adj(this) {
-            if res.contains(&a) {
-                continue;
-            }
-            res.insert(this);
-            this = a;
-            break;
+    let start_pipe = start_pipe(grid, start_pos);
+    let mut res = vec![start_pos];
+    let mut prev = start_pos;
+    let mut this = start_pipe.adj(start_pos)[0];
+    while this != start_pos {
+        res.push(this);
+        let next = grid[this]
+            .adj(this)
+            .into_iter()
+            .find(|a| *a != prev)
+            .unwrap();
+        prev = this;
+        this = next;
+    }
+    res
+}
+
+/**
+The pipe under the start, which is the one that connects to two pipes connecting back to it
+*/
+fn start_pipe(grid: &Grid<Pipe>, start_pos: Point) -> Pipe {
+    [Pipe::NS, Pipe::EW, Pipe::NE, Pipe::NW, Pipe::SE, Pipe::SW]
+        .into_iter()
+        .find(|pipe| {
+            pipe.adj(start_pos).into_iter().all(|a| {
+                grid.get(a)
+                    .is_some_and(|p| p.is_pipe() && p.adj(a).contains(&start_pos))
+            })
+        })
+        .expect("start is not on a loop")
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Winding {
+    Clockwise,
+    Counterclockwise,
+}
+
+/**
+Which way round the loop goes, as seen on the map. The map's y axis points down, so a loop with a
+positive shoelace area goes clockwise.
+*/
+fn winding(tiles: &[Point]) -> Winding {
+    let vertices: Vec<Point<i64>> = tiles.iter().copied().map(Point::into).collect();
+    if signed_double_area(&vertices) > 0 {
+        Winding::Clockwise
+    } else {
+        Winding::Counterclockwise
+    }
+}
+
+/**
+The tiles enclosed by the loop. Going round a clockwise loop the inside is always on the right, and
+on the left of a counterclockwise one, so every tile on that side of the loop is inside, along with
+everything that can be reached from them without crossing the loop.
+*/
+fn inside_tiles(tiles: &[Point]) -> HashSet<Point> {
+    let on_loop: HashSet<_> = tiles.iter().copied().collect();
+    let winding = winding(tiles);
+    let mut q = Vec::new();
+    for (a, b) in tiles.iter().zip(tiles.iter().cycle().skip(1)) {
+        let dir = Dir::values()
+            .into_iter()
+            .find(|d| *a + d.diff() == *b)
+            .expect("loop tiles are not adjacent");
+        let inward = match winding {
+            Winding::Clockwise => dir.turn_right(),
+            Winding::Counterclockwise => dir.turn_left(),
+        };
+        // both ends of the step, so that the inside of a corner is not missed
+        q.extend([*a + inward.diff(), *b + inward.diff()]);
+    }
+
+    let mut inside = HashSet::new();
+    while let Some(p) = q.pop() {
+        if on_loop.contains(&p) || !inside.insert(p) {
+            continue;
         }
+        q.extend(p.adj());
     }
-    res.insert(last);
-    (res, start_pos)
+    inside
+}
+
+fn render(grid: &Grid<Pipe>, tiles: &[Point], inside: &HashSet<Point>) -> String {
+    let on_loop: HashSet<_> = tiles.iter().collect();
+    (0..grid.height())
+        .map(|y| {
+            (0..grid.width())
+                .map(|x| {
+                    let p = Point(x, y);
+                    if on_loop.contains(&p) {
+                        '#'
+                    } else if inside.contains(&p) {
+                        'I'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -101,51 +165,8 @@ impl Pipe {
         }
     }
 
-    #[allow(clippy::many_single_char_names)]
-    fn left_right(self, p: Point, from: Dir) -> (Vec<Point>, Vec<Point>) {
-        let n = p + Dir::N.diff();
-        let e = p + Dir::E.diff();
-        let s = p + Dir::S.diff();
-        let w = p + Dir::W.diff();
-        match (self, from) {
-            (Pipe::NS, Dir::N) => (vec![e], vec![w]),
-            (Pipe::NS, Dir::S) => (vec![w], vec![e]),
-            (Pipe::EW, Dir::E) => (vec![s], vec![n]),
-            (Pipe::EW, Dir::W) => (vec![n], vec![s]),
-            (Pipe::NE, Dir::N) => (vec![], vec![s, w]),
-            (Pipe::NE, Dir::E) => (vec![s, w], vec![]),
-            (Pipe::NW, Dir::N) => (vec![s, e], vec![]),
-            (Pipe::NW, Dir::W) => (vec![], vec![s, e]),
-            (Pipe::SE, Dir::S) => (vec![n, w], vec![]),
-            (Pipe::SE, Dir::E) => (vec![], vec![n, w]),
-            (Pipe::SW, Dir::S) => (vec![], vec![n, e]),
-            (Pipe::SW, Dir::W) => (vec![n, e], vec![]),
-            _ => panic!("left_right {p} {from:?}"),
-        }
-    }
-
-    #[allow(clippy::many_single_char_names)]
-    #[allow(clippy::match_same_arms)]
-    fn go(self, p: Point, from: Dir) -> (Point, Dir) {
-        let n = (p + Dir::N.diff(), Dir::S);
-        let e = (p + Dir::E.diff(), Dir::W);
-        let s = (p + Dir::S.diff(), Dir::N);
-        let w = (p + Dir::W.diff(), Dir::E);
-        match (self, from) {
-            (Pipe::NS, Dir::N) => s,
-            (Pipe::NS, Dir::S) => n,
-            (Pipe::EW, Dir::E) => w,
-            (Pipe::EW, Dir::W) => e,
-            (Pipe::NE, Dir::N) => e,
-            (Pipe::NE, Dir::E) => n,
-            (Pipe::NW, Dir::N) => w,
-            (Pipe::NW, Dir::W) => n,
-            (Pipe::SE, Dir::S) => e,
-            (Pipe::SE, Dir::E) => s,
-            (Pipe::SW, Dir::S) => w,
-            (Pipe::SW, Dir::W) => s,
-            _ => panic!("go {p} {from:?}"),
-        }
+    fn is_pipe(self) -> bool {
+        !matches!(self, Pipe::Ground | Pipe::Start)
     }
 }
 
@@ -165,17 +186,110 @@ impl From<char> for Pipe {
     }
 }
 
-fn flood(grid: &Grid<Pipe>, start: Vec<Point>, flooded: &mut HashSet<Point>) {
-    let mut q = start;
-    while let Some(p) = q.pop() {
-        if grid.get(p) != Some(&Pipe::Ground) {
-            continue;
-        }
-        if !flooded.insert(p) {
-            continue;
-        }
-        for a in p.adj() {
-            q.push(a);
-        }
+#[cfg(test)]
+mod test {
+    use super::{get_loop, inside_tiles, start_pipe, winding, Pipe, Winding};
+    use crate::util::{Grid, Point};
+    use crate::Solution as _;
+
+    #[test]
+    fn ex1() {
+        let ex = "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...";
+        assert_eq!("8", super::Solution.solve_1(ex));
+    }
+
+    #[test]
+    fn ex2() {
+        let ex = "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+        assert_eq!("10", super::Solution.solve_2(ex));
+    }
+
+    const SQUARE: &str = ".....
+.S-7.
+.|.|.
+.L-J.
+.....";
+
+    const SQUEEZED: &str = "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+
+    const LARGER: &str = ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+
+    /**
+    The same map flipped left to right, so its loop winds the other way
+    */
+    fn mirrored(input: &str) -> String {
+        let mirror = |c| match c {
+            'L' => 'J',
+            'J' => 'L',
+            'F' => '7',
+            '7' => 'F',
+            c => c,
+        };
+        input
+            .lines()
+            .map(|line| line.chars().rev().map(mirror).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn check(input: &str, start: Point, pipe: Pipe, expected_winding: Winding, enclosed: usize) {
+        let grid: Grid<Pipe> = input.parse().unwrap();
+        assert!(start_pipe(&grid, start) == pipe);
+        let tiles = get_loop(&grid);
+        assert_eq!(expected_winding, winding(&tiles));
+        assert_eq!(enclosed, inside_tiles(&tiles).len());
+        assert_eq!(enclosed.to_string(), super::Solution.solve_2(input));
+    }
+
+    #[test]
+    fn start_and_winding() {
+        assert_eq!("4", super::Solution.solve_1(SQUARE));
+        check(SQUARE, Point(1, 1), Pipe::SE, Winding::Counterclockwise, 1);
+        check(
+            SQUEEZED,
+            Point(1, 1),
+            Pipe::SE,
+            Winding::Counterclockwise,
+            4,
+        );
+        check(LARGER, Point(12, 4), Pipe::SE, Winding::Clockwise, 8);
+        let mirrored = mirrored(LARGER);
+        check(
+            &mirrored,
+            Point(7, 4),
+            Pipe::SW,
+            Winding::Counterclockwise,
+            8,
+        );
     }
 }
@@ -4,8 +4,8 @@ use itertools::Itertools;
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let mut galaxies = parse_input(&input);
+    fn solve_1(&self, input: &str) -> String {
+        let mut galaxies = parse_input(input);
         expand(&mut galaxies, 2);
         galaxies
             .into_iter()
@@ -15,8 +15,8 @@ impl crate::Solution for Solution {
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let mut galaxies = parse_input(&input);
+    fn solve_2(&self, input: &str) -> String {
+        let mut galaxies = parse_input(input);
         expand(&mut galaxies, 1_000_000);
         galaxies
             .into_iter()
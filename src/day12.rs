@@ -1,4 +1,4 @@
-use crate::util::parse_lines;
+use crate::util::{arrangements, parse_lines, Cell};
 use itertools::Itertools;
 use std::fmt::{Debug, Formatter};
 use std::str::FromStr;
@@ -6,15 +6,15 @@ use std::str::FromStr;
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        parse_lines::<Springs>(&input)
+    fn solve_1(&self, input: &str) -> String {
+        parse_lines::<Springs>(input)
             .map(|springs| springs.possibilities())
             .sum::<u64>()
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        parse_lines::<Springs>(&input)
+    fn solve_2(&self, input: &str) -> String {
+        parse_lines::<Springs>(input)
             .update(Springs::expand)
             .map(|springs| springs.possibilities())
             .sum::<u64>()
@@ -23,28 +23,13 @@ impl crate::Solution for Solution {
 }
 
 struct Springs {
-    conditions: Vec<Condition>,
+    /**
+    Operational springs are empty cells and damaged ones are filled
+    */
+    conditions: Vec<Cell>,
     checks: Vec<usize>,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum Condition {
-    Operational,
-    Damaged,
-    Unknown,
-}
-
-impl Debug for Condition {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let c = match self {
-            Condition::Operational => '.',
-            Condition::Damaged => '#',
-            Condition::Unknown => '?',
-        };
-        write!(f, "{c}")
-    }
-}
-
 impl FromStr for Springs {
     type Err = String;
 
@@ -53,9 +38,7 @@ impl FromStr for Springs {
         let conditions = conditions_str
             .chars()
             .map(|c| match c {
-                '.' => Condition::Operational,
-                '#' => Condition::Damaged,
-                '?' => Condition::Unknown,
+                '.' | '#' | '?' => Cell::from(c),
                 _ => panic!("{s}"),
             })
             .collect();
@@ -83,48 +66,98 @@ impl Springs {
         let conditions = self.conditions.clone();
         let checks = self.checks.clone();
         for _ in 0..4 {
-            self.conditions.push(Condition::Unknown);
+            self.conditions.push(Cell::Unknown);
             self.conditions.extend(conditions.iter().copied());
             self.checks.extend(checks.iter().copied());
         }
     }
 
-    #[allow(clippy::match_on_vec_items)] // false positive
     fn possibilities(&self) -> u64 {
-        let conds = &self.conditions;
-        let checks = &self.checks;
-        let mut table = vec![vec![0; checks.len() + 1]; conds.len() + 1];
-        table[0][0] = 1;
+        let count = arrangements(&self.conditions, &self.checks);
+        log::info!("{self:?} -> {count}");
+        count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Springs;
+    use crate::util::{Arrangements, Cell};
+    use crate::Solution as _;
+    use itertools::Itertools;
+
+    const EX: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
 
-        for check_i in 0..=checks.len() {
-            let check = if check_i == 0 { 0 } else { checks[check_i - 1] };
-            for cond_i in 1..=conds.len() {
-                if cond_i < check {
-                    continue;
-                }
+    /**
+    Every way to fill in the unknown springs that matches the checks, in the same order as
+    `Arrangements`
+    */
+    fn brute_force(springs: &Springs) -> Vec<Vec<Cell>> {
+        let unknowns = springs
+            .conditions
+            .iter()
+            .filter(|c| **c == Cell::Unknown)
+            .count();
+        (0..1u32 << unknowns)
+            .map(|bits| {
+                let mut bit = unknowns;
+                springs
+                    .conditions
+                    .iter()
+                    .map(|c| match c {
+                        Cell::Unknown => {
+                            bit -= 1;
+                            if bits & (1 << bit) == 0 {
+                                Cell::Empty
+                            } else {
+                                Cell::Filled
+                            }
+                        }
+                        c => *c,
+                    })
+                    .collect_vec()
+            })
+            .filter(|cells| {
+                let runs = cells
+                    .iter()
+                    .dedup_with_count()
+                    .filter(|(_, c)| **c == Cell::Filled)
+                    .map(|(n, _)| n)
+                    .collect_vec();
+                runs == springs.checks
+            })
+            .collect()
+    }
 
-                let op = table[cond_i - 1][check_i];
-                let is_blocked = conds[cond_i - check..cond_i].contains(&Condition::Operational);
-                let da = if check == 0 || is_blocked {
-                    0
-                } else if cond_i == check {
-                    table[0][check_i - 1]
-                } else if conds[cond_i - check - 1] == Condition::Damaged {
-                    0
-                } else {
-                    table[cond_i - check - 1][check_i - 1]
-                };
+    #[test]
+    fn ex() {
+        assert_eq!("21", super::Solution.solve_1(EX));
+        assert_eq!("525152", super::Solution.solve_2(EX));
+    }
 
-                table[cond_i][check_i] = match conds[cond_i - 1] {
-                    Condition::Operational => op,
-                    Condition::Damaged => da,
-                    Condition::Unknown => op + da,
-                }
-            }
+    #[test]
+    fn arrangements_match_brute_force() {
+        let mut seed = 12u64;
+        let mut below = |n| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) % n
+        };
+        for springs in EX.lines().map(|line| line.parse::<Springs>().unwrap()) {
+            let expected = brute_force(&springs);
+            let arrangements = Arrangements::new(&springs.conditions, &springs.checks);
+            assert_eq!(expected.len() as u64, springs.possibilities());
+            assert_eq!(expected, arrangements.iter().collect_vec());
+            let last = expected.len() as u64 - 1;
+            assert_eq!(expected.last(), arrangements.nth(last).as_ref());
+            let sample = arrangements.sample(&mut below).unwrap();
+            assert!(expected.contains(&sample), "{sample:?}");
         }
-        log::debug!("{table:?}");
-        let count = table[conds.len()][checks.len()];
-        log::info!("{self:?} -> {count}");
-        count
     }
 }
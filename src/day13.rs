@@ -1,83 +1,68 @@
-use crate::util::{Grid, Point};
-use std::collections::HashSet;
+use crate::util::{reflections, sections, Grid, Symmetry};
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        solve(&input, 0)
+    fn solve_1(&self, input: &str) -> String {
+        solve(input, 0)
     }
 
-    fn solve_2(&self, input: String) -> String {
-        solve(&input, 1)
+    fn solve_2(&self, input: &str) -> String {
+        solve(input, 1)
     }
 }
 
 fn solve(input: &str, target_diff: usize) -> String {
     parse_input(input)
-        .map(|ps| {
-            let (orient, val) = find_mirror(&ps, target_diff);
-            match orient {
-                Orientation::Vertical => val,
-                Orientation::Horizontal => val * 100,
-            }
+        .map(|grid| match find_mirror(&grid, target_diff) {
+            Symmetry::Vertical(x) => x,
+            Symmetry::Horizontal(y) => y * 100,
+            s => unreachable!("{s:?}"),
         })
         .sum::<i32>()
         .to_string()
 }
 
-fn parse_input(s: &str) -> impl Iterator<Item = HashSet<Point>> + '_ {
-    s.split("\n\n").map(|map_str| {
-        map_str
-            .parse::<Grid<char>>()
-            .unwrap()
-            .points_with_item()
-            .filter(|(_, c)| c == &&'#')
-            .map(|(p, _)| p)
-            .collect()
-    })
+fn parse_input(s: &str) -> impl Iterator<Item = Grid<char>> + '_ {
+    sections(s).map(|map_str| map_str.parse().unwrap())
 }
 
-fn find_mirror(points: &HashSet<Point>, target_diff: usize) -> (Orientation, i32) {
-    let target = points.len() - target_diff;
-    {
-        let max_x = points.iter().map(|p| p.0).max().unwrap();
-        for x in 1..=max_x {
-            let mirror_points = points
-                .iter()
-                .filter(|p| {
-                    let d = x - p.0;
-                    let mx = x + d - 1;
-                    mx < 0 || mx > max_x || points.contains(&Point(mx, p.1))
-                })
-                .count();
-            if mirror_points == target {
-                return (Orientation::Vertical, x);
-            }
-        }
-    }
+/**
+The reflection line that has exactly `target_diff` smudges, each of which is a pair of cells that
+should match but don't
+*/
+fn find_mirror(grid: &Grid<char>, target_diff: usize) -> Symmetry {
+    let report = reflections(grid)
+        .into_iter()
+        .find(|r| r.mismatches.len() == target_diff)
+        .unwrap();
+    log::debug!("{:?} smudged at {:?}", report.symmetry, report.mismatches);
+    report.symmetry
+}
 
-    {
-        let max_y = points.iter().map(|p| p.1).max().unwrap();
-        for y in 1..=max_y {
-            let mirror_points = points
-                .iter()
-                .filter(|p| {
-                    let d = y - p.1;
-                    let my = y + d - 1;
-                    my < 0 || my > max_y || points.contains(&Point(p.0, my))
-                })
-                .count();
-            if mirror_points == target {
-                return (Orientation::Horizontal, y);
-            }
-        }
-    }
+#[cfg(test)]
+mod test {
+    use crate::Solution as _;
 
-    unreachable!()
-}
+    const EX: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
 
-enum Orientation {
-    Vertical,
-    Horizontal,
+    #[test]
+    fn ex() {
+        assert_eq!("405", super::Solution.solve_1(EX));
+        assert_eq!("400", super::Solution.solve_2(EX));
+    }
 }
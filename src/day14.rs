@@ -1,38 +1,31 @@
-use crate::util::{Grid, Point};
-use std::collections::HashMap;
+use crate::util::{find_cycle, Grid, Point};
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
+    fn solve_1(&self, input: &str) -> String {
         let mut map: Grid<Tile> = input.parse().unwrap();
         full_tilt(&mut map, Point(0, -1));
         load(&map).to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let mut map: Grid<Tile> = input.parse().unwrap();
-        let mut seen = HashMap::new();
-        let mut cycle_start = 0;
-        let mut cycle_length = 0;
-        for i in 1.. {
-            spin_cycle(&mut map);
-            let key = key(&map);
-            if let Some(old) = seen.insert(key, i) {
-                log::debug!("duplicate {key} at {i}, prev at {old}");
-                cycle_start = old;
-                cycle_length = i - old;
-                break;
-            }
-        }
-        let rem = (1_000_000_000 - cycle_start) % cycle_length;
-        for _ in 0..rem {
-            spin_cycle(&mut map);
-        }
-        load(&map).to_string()
+    fn solve_2(&self, input: &str) -> String {
+        let map: Grid<Tile> = input.parse().unwrap();
+        let cycle = find_cycle(
+            map,
+            |map| {
+                let mut map = map.clone();
+                spin_cycle(&mut map);
+                map
+            },
+            rocks,
+        );
+        log::debug!("{:?}", cycle.period);
+        load(cycle.nth_state(1_000_000_000)).to_string()
     }
 }
 
+#[derive(Clone)]
 enum Tile {
     Empty,
     Rock,
@@ -56,16 +49,10 @@ fn load(map: &Grid<Tile>) -> i32 {
         .sum()
 }
 
-fn key(map: &Grid<Tile>) -> i32 {
+fn rocks(map: &Grid<Tile>) -> Vec<Point> {
     map.points_with_item()
-        .map(|(p, t)| {
-            if let Tile::Rock = t {
-                p.0 + 1000 * p.1
-            } else {
-                0
-            }
-        })
-        .sum()
+        .filter_map(|(p, t)| if let Tile::Rock = t { Some(p) } else { None })
+        .collect()
 }
 
 fn tilt(map: &mut Grid<Tile>, dir: Point) -> bool {
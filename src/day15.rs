@@ -1,48 +1,25 @@
-use std::str::FromStr;
+use std::fmt::{Display, Formatter};
+#[cfg(test)]
+use std::io::{self, BufRead};
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
+    fn solve_1(&self, input: &str) -> String {
         input.trim().split(',').map(hash).sum::<usize>().to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        const EMPTY_VEC: Vec<Lens> = Vec::new();
-        let mut boxes = [EMPTY_VEC; 256];
-
-        for instr in input.split(',').map(|s| s.parse::<Instruction>().unwrap()) {
-            let label = instr.label;
-            let hash = hash(&label);
-            let maybe_pos = boxes[hash].iter().position(|l| l.label == label);
-
-            match instr.typ {
-                InstructionType::Remove => {
-                    if let Some(pos) = maybe_pos {
-                        boxes[hash].remove(pos);
-                    }
-                }
-                InstructionType::Insert(focal) => {
-                    if let Some(pos) = maybe_pos {
-                        boxes[hash][pos].focal = focal;
-                    } else {
-                        boxes[hash].push(Lens { label, focal });
-                    }
-                }
+    fn solve_2(&self, input: &str) -> String {
+        let mut boxes = LensBoxes::new(256, hash);
+        let instrs = input.trim().split(',').filter_map(Instruction::parse);
+        if log::log_enabled!(log::Level::Debug) {
+            for (i, snapshot) in boxes.snapshots(instrs).enumerate() {
+                log::debug!("After step {}:\n{snapshot}", i + 1);
             }
+        } else {
+            boxes.run(instrs);
         }
-
-        boxes
-            .into_iter()
-            .enumerate()
-            .map(|(bi, b)| {
-                b.into_iter()
-                    .enumerate()
-                    .map(|(li, l)| (bi + 1) * (li + 1) * (l.focal as usize))
-                    .sum::<usize>()
-            })
-            .sum::<usize>()
-            .to_string()
+        boxes.focusing_power().to_string()
     }
 }
 
@@ -50,8 +27,25 @@ fn hash(s: &str) -> usize {
     s.chars().fold(0, |acc, c| (acc + (c as usize)) * 17 % 256)
 }
 
-struct Instruction {
-    label: String,
+/**
+The comma-separated steps of an initialization sequence, read one at a time so that the whole
+sequence never has to be in memory. Line breaks are ignored, and so are empty steps.
+*/
+#[cfg(test)]
+fn read_steps(reader: impl BufRead) -> impl Iterator<Item = io::Result<String>> {
+    reader
+        .split(b',')
+        .map(|step| {
+            let mut step = String::from_utf8(step?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            step.retain(|c| c != '\n' && c != '\r');
+            Ok(step)
+        })
+        .filter(|step| !matches!(step, Ok(step) if step.trim().is_empty()))
+}
+
+struct Instruction<L> {
+    label: L,
     typ: InstructionType,
 }
 
@@ -60,27 +54,202 @@ enum InstructionType {
     Insert(u8),
 }
 
-struct Lens {
-    label: String,
+#[derive(Clone)]
+struct Lens<L> {
+    label: L,
     focal: u8,
 }
 
-impl FromStr for Instruction {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let instr = if s.ends_with(|c: char| c.is_ascii_digit()) {
-            let (label, focal_str) = s.split_once('=').unwrap();
+impl Instruction<&str> {
+    /**
+    The instruction in a step, or `None` if the step is blank, as after a trailing comma
+    */
+    fn parse(s: &str) -> Option<Instruction<&str>> {
+        let s = s.trim();
+        if s.is_empty() {
+            return None;
+        }
+        let instr = if let Some((label, focal_str)) = s.split_once('=') {
             Instruction {
-                label: label.to_string(),
-                typ: InstructionType::Insert(focal_str.parse().unwrap()),
+                label,
+                typ: InstructionType::Insert(focal_str.trim().parse().unwrap()),
             }
         } else {
             Instruction {
-                label: s.trim_end_matches('-').to_string(),
+                label: s.trim_end_matches('-'),
                 typ: InstructionType::Remove,
             }
         };
-        Ok(instr)
+        Some(instr)
+    }
+
+    /**
+    The instruction with its own copy of the label, for steps that don't outlive their source
+    */
+    #[cfg(test)]
+    fn into_owned(self) -> Instruction<String> {
+        Instruction {
+            label: self.label.to_string(),
+            typ: self.typ,
+        }
+    }
+}
+
+/**
+A row of boxes that lenses are put in and taken out of by the HASHMAP procedure. The box for a label
+is its hash, modulo the number of boxes. Labels are anything that can be seen as a `str`, so they
+can borrow from the instructions or own their text.
+*/
+#[derive(Clone)]
+struct LensBoxes<L> {
+    boxes: Vec<Vec<Lens<L>>>,
+    hash: fn(&str) -> usize,
+}
+
+impl<L: AsRef<str>> LensBoxes<L> {
+    fn new(count: usize, hash: fn(&str) -> usize) -> LensBoxes<L> {
+        LensBoxes {
+            boxes: (0..count).map(|_| Vec::new()).collect(),
+            hash,
+        }
+    }
+
+    fn step(&mut self, instr: Instruction<L>) {
+        let label = instr.label.as_ref();
+        let i = (self.hash)(label) % self.boxes.len();
+        let b = &mut self.boxes[i];
+        let maybe_pos = b.iter().position(|l| l.label.as_ref() == label);
+
+        match instr.typ {
+            InstructionType::Remove => {
+                if let Some(pos) = maybe_pos {
+                    b.remove(pos);
+                }
+            }
+            InstructionType::Insert(focal) => {
+                if let Some(pos) = maybe_pos {
+                    b[pos].focal = focal;
+                } else {
+                    b.push(Lens {
+                        label: instr.label,
+                        focal,
+                    });
+                }
+            }
+        }
+    }
+
+    /**
+    Runs every instruction from `instrs`, which may be a lazy stream of them
+    */
+    fn run(&mut self, instrs: impl IntoIterator<Item = Instruction<L>>) {
+        for instr in instrs {
+            self.step(instr);
+        }
+    }
+
+    fn focusing_power(&self) -> usize {
+        self.boxes
+            .iter()
+            .enumerate()
+            .map(|(bi, b)| {
+                b.iter()
+                    .enumerate()
+                    .map(|(li, l)| (bi + 1) * (li + 1) * (l.focal as usize))
+                    .sum::<usize>()
+            })
+            .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+impl LensBoxes<String> {
+    /**
+    Runs a log of steps streamed from `reader`, for logs too long to read in whole
+    */
+    fn replay(&mut self, reader: impl BufRead) -> io::Result<()> {
+        for step in read_steps(reader) {
+            let step = step?;
+            if let Some(instr) = Instruction::parse(&step) {
+                self.step(instr.into_owned());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<L: AsRef<str> + Clone> LensBoxes<L> {
+    /**
+    Runs the instructions one at a time, giving a copy of the boxes after each one
+    */
+    fn snapshots<'s>(
+        &'s mut self,
+        instrs: impl IntoIterator<Item = Instruction<L>> + 's,
+    ) -> impl Iterator<Item = LensBoxes<L>> + 's {
+        instrs.into_iter().map(|instr| {
+            self.step(instr);
+            self.clone()
+        })
+    }
+}
+
+/**
+Each box with lenses in it, like `Box 3: [ot 7] [ab 5]`
+*/
+impl<L: AsRef<str>> Display for LensBoxes<L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, b) in self.boxes.iter().enumerate() {
+            if b.is_empty() {
+                continue;
+            }
+            write!(f, "Box {i}:")?;
+            for lens in b {
+                write!(f, " [{} {}]", lens.label.as_ref(), lens.focal)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hash, Instruction, LensBoxes};
+    use crate::Solution as _;
+
+    const EX: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7\n";
+
+    #[test]
+    fn ex() {
+        assert_eq!("1320", super::Solution.solve_1(EX));
+        assert_eq!("145", super::Solution.solve_2(EX));
+        let trailing = EX.trim().to_string() + ",\n";
+        assert_eq!("145", super::Solution.solve_2(&trailing));
+    }
+
+    #[test]
+    fn machine() {
+        let mut boxes = LensBoxes::new(256, hash);
+        let instrs = EX.trim().split(',').filter_map(Instruction::parse);
+        let snapshots: Vec<_> = boxes.snapshots(instrs).map(|s| s.to_string()).collect();
+        assert_eq!("Box 0: [rn 1]\n", snapshots[0]);
+        assert_eq!("Box 0: [rn 1] [cm 2]\nBox 1: [qp 3]\n", snapshots[3]);
+        assert_eq!(
+            "Box 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n",
+            boxes.to_string()
+        );
+
+        // the same steps streamed from a reader, into four boxes hashed by label length
+        let mut small = LensBoxes::new(4, str::len);
+        let log = EX.replace(',', ",\r\n") + ",";
+        small.replay(log.as_bytes()).unwrap();
+        assert_eq!(
+            "Box 2: [rn 1] [cm 2] [ot 7] [ab 5] [pc 6]\n",
+            small.to_string()
+        );
+        assert_eq!(
+            3 * (1 + 2 * 2 + 3 * 7 + 4 * 5 + 5 * 6),
+            small.focusing_power()
+        );
     }
 }
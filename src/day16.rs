@@ -1,26 +1,27 @@
-use crate::util::{Dir, Grid, Point};
-use std::collections::HashSet;
+use crate::util::{Dir, Graph, Grid, Point};
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
+    fn solve_1(&self, input: &str) -> String {
         let map = input.parse().unwrap();
-        energy(&trace_light(&map, Point(0, 0), Dir::E)).to_string()
+        let start = (Point(0, 0), Dir::E);
+        BeamTracer::new(&map, [start]).energy(start).to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
+    fn solve_2(&self, input: &str) -> String {
         let map: Grid<Tile> = input.parse().unwrap();
         let w = map.width();
         let h = map.height();
         let top = (0..w).map(|x| (Point(x, 0), Dir::S));
-        let bot = (0..w).map(|x| (Point(x, (h) - 1), Dir::N));
+        let bot = (0..w).map(|x| (Point(x, h - 1), Dir::N));
         let lft = (0..h).map(|y| (Point(0, y), Dir::E));
-        let rgt = (0..h).map(|y| (Point((h) - 1, y), Dir::W));
-        top.chain(bot)
-            .chain(lft)
-            .chain(rgt)
-            .map(|(start_pos, start_dir)| energy(&trace_light(&map, start_pos, start_dir)))
+        let rgt = (0..h).map(|y| (Point(w - 1, y), Dir::W));
+        let starts: Vec<_> = top.chain(bot).chain(lft).chain(rgt).collect();
+        let tracer = BeamTracer::new(&map, starts.iter().copied());
+        starts
+            .into_iter()
+            .map(|start| tracer.energy(start))
             .max()
             .unwrap()
             .to_string()
@@ -47,66 +48,230 @@ impl From<char> for Tile {
     }
 }
 
-fn trace_light(map: &Grid<Tile>, start_pos: Point, start_dir: Dir) -> Grid<HashSet<Dir>> {
-    let mut trace = Grid::build(map.width(), map.height(), |_| HashSet::new());
-    let mut q = vec![(start_pos, start_dir)];
-
-    while let Some((p, current_dir)) = q.pop() {
-        let Some(tile) = map.get(p) else {
-            continue;
-        };
-        if !trace[p].insert(current_dir) {
-            continue;
+impl Tile {
+    /**
+    The direction a beam moving in `dir` leaves this tile in, and the second direction if the beam
+    is split
+    */
+    fn outgoing(&self, dir: Dir) -> (Dir, Option<Dir>) {
+        match (self, dir) {
+            (Tile::MirrorDown, Dir::N) | (Tile::MirrorUp, Dir::S) => (Dir::W, None),
+            (Tile::MirrorDown, Dir::E) | (Tile::MirrorUp, Dir::W) => (Dir::S, None),
+            (Tile::MirrorDown, Dir::S) | (Tile::MirrorUp, Dir::N) => (Dir::E, None),
+            (Tile::MirrorDown, Dir::W) | (Tile::MirrorUp, Dir::E) => (Dir::N, None),
+            (Tile::SplitterH, Dir::N | Dir::S) => (Dir::E, Some(Dir::W)),
+            (Tile::SplitterV, Dir::E | Dir::W) => (Dir::N, Some(Dir::S)),
+            _ => (dir, None),
         }
-        match tile {
-            Tile::Empty => {
-                q.push((p + current_dir.diff(), current_dir));
-            }
-            Tile::MirrorDown => {
-                let new_dir = match current_dir {
-                    Dir::N => Dir::W,
-                    Dir::E => Dir::S,
-                    Dir::S => Dir::E,
-                    Dir::W => Dir::N,
+    }
+}
+
+/**
+A beam's path from where it enters a tile until it is split or leaves the grid. Mirrors and
+splitters met edge-on only bend or pass the beam, so they don't end a segment.
+*/
+struct Segment {
+    cells: Vec<Point>,
+    splits: Vec<(Point, Dir)>,
+}
+
+fn trace_segment(map: &Grid<Tile>, start: (Point, Dir)) -> Segment {
+    let (mut p, mut dir) = start;
+    let mut cells = Vec::new();
+    while let Some(tile) = map.get(p) {
+        cells.push(p);
+        match tile.outgoing(dir) {
+            (a, Some(b)) => {
+                return Segment {
+                    cells,
+                    splits: vec![(p + a.diff(), a), (p + b.diff(), b)],
                 };
-                q.push((p + new_dir.diff(), new_dir));
             }
-            Tile::MirrorUp => {
-                let new_dir = match current_dir {
-                    Dir::N => Dir::E,
-                    Dir::E => Dir::N,
-                    Dir::S => Dir::W,
-                    Dir::W => Dir::S,
-                };
-                q.push((p + new_dir.diff(), new_dir));
+            (a, None) => {
+                dir = a;
+                p = p + dir.diff();
             }
-            Tile::SplitterH => match current_dir {
-                Dir::N | Dir::S => {
-                    q.push((p + Dir::E.diff(), Dir::E));
-                    q.push((p + Dir::W.diff(), Dir::W));
-                }
-                _ => {
-                    q.push((p + current_dir.diff(), current_dir));
-                }
-            },
-            Tile::SplitterV => match current_dir {
-                Dir::E | Dir::W => {
-                    q.push((p + Dir::N.diff(), Dir::N));
-                    q.push((p + Dir::S.diff(), Dir::S));
+        }
+        // without splits a beam's path can be followed backwards, so the only state it can come
+        // back to is the one it started in
+        if (p, dir) == start {
+            break;
+        }
+    }
+    Segment {
+        cells,
+        splits: Vec::new(),
+    }
+}
+
+/**
+The tiles energized by beams entering a contraption at each of a set of starts. Beams are traced a
+segment at a time, and each segment is a node in a graph with an edge to the segments its split
+starts, so a beam energizes the cells of every segment reachable from its first one. Reachable cells
+are gathered once per strongly connected component of that graph, so cycles of splitters are
+handled and segments shared between starts are only traced once.
+*/
+struct BeamTracer {
+    segments: Graph<(Point, Dir)>,
+    component: Vec<usize>,
+    energized: Vec<Cells>,
+}
+
+impl BeamTracer {
+    fn new(map: &Grid<Tile>, starts: impl IntoIterator<Item = (Point, Dir)>) -> BeamTracer {
+        let mut segments = Graph::new();
+        for start in starts {
+            segments.add_node(start);
+        }
+        let mut cells = Vec::new();
+        // segments are added to the graph as they are found, and traced in that order
+        let mut i = 0;
+        while i < segments.len() {
+            let segment = trace_segment(map, *segments.node(i));
+            for split in segment.splits {
+                let j = segments.add_node(split);
+                segments.add_edge(i, j, ());
+            }
+            cells.push(segment.cells);
+            i += 1;
+        }
+
+        let index = |p: &Point| usize::try_from(p.1 * map.width() + p.0).unwrap();
+        let size = usize::try_from(map.width() * map.height()).unwrap();
+        let components = segments.strongly_connected_components();
+        let mut component = vec![0; segments.len()];
+        for (c, members) in components.iter().enumerate() {
+            for &m in members {
+                component[m] = c;
+            }
+        }
+        // components come in reverse topological order, so the ones a component leads to are done
+        let mut energized: Vec<Cells> = Vec::with_capacity(components.len());
+        for (c, members) in components.iter().enumerate() {
+            let mut set = Cells::new(size);
+            for &m in members {
+                for p in &cells[m] {
+                    set.insert(index(p));
                 }
-                _ => {
-                    q.push((p + current_dir.diff(), current_dir));
+                for (n, ()) in segments.neighbors(m) {
+                    if component[n] != c {
+                        set.union_with(&energized[component[n]]);
+                    }
                 }
-            },
+            }
+            energized.push(set);
         }
+
+        BeamTracer {
+            segments,
+            component,
+            energized,
+        }
+    }
+
+    /**
+    The number of tiles energized by a beam from `start`, which must be one of the starts the
+    tracer was built with
+    */
+    fn energy(&self, start: (Point, Dir)) -> usize {
+        let node = self.segments.index_of(&start).expect("not a traced start");
+        self.energized[self.component[node]].len()
+    }
+}
+
+/**
+A set of tiles, by index in the grid
+*/
+struct Cells(Vec<u64>);
+
+impl Cells {
+    fn new(size: usize) -> Cells {
+        Cells(vec![0; size.div_ceil(64)])
     }
 
-    trace
+    fn insert(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    fn union_with(&mut self, other: &Cells) {
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a |= b;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
 }
 
-fn energy(trace: &Grid<HashSet<Dir>>) -> usize {
-    trace
-        .points_with_item()
-        .filter(|(_, set)| !set.is_empty())
-        .count()
+#[cfg(test)]
+mod test {
+    use super::{BeamTracer, Tile};
+    use crate::util::{Dir, Grid, Point};
+    use crate::Solution as _;
+    use std::collections::HashSet;
+
+    const EX: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....
+";
+
+    // a loop of mirrors through a splitter that it meets edge-on, fed by another splitter
+    const LOOP: &str = r"./..\.
+......
+.\-./.
+..|...
+";
+
+    fn brute_force(map: &Grid<Tile>, start: (Point, Dir)) -> usize {
+        let mut seen = HashSet::new();
+        let mut q = vec![start];
+        while let Some((p, dir)) = q.pop() {
+            let Some(tile) = map.get(p) else {
+                continue;
+            };
+            if !seen.insert((p, dir)) {
+                continue;
+            }
+            let (a, b) = tile.outgoing(dir);
+            q.extend(
+                [Some(a), b]
+                    .into_iter()
+                    .flatten()
+                    .map(|d| (p + d.diff(), d)),
+            );
+        }
+        seen.into_iter()
+            .map(|(p, _)| p)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    #[test]
+    fn ex() {
+        assert_eq!("46", super::Solution.solve_1(EX));
+        assert_eq!("51", super::Solution.solve_2(EX));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for input in [EX, LOOP] {
+            let map: Grid<Tile> = input.parse().unwrap();
+            let starts: Vec<_> = map
+                .points()
+                .flat_map(|p| Dir::values().map(|d| (p, d)))
+                .collect();
+            let tracer = BeamTracer::new(&map, starts.iter().copied());
+            for start in starts {
+                assert_eq!(brute_force(&map, start), tracer.energy(start), "{start:?}");
+            }
+        }
+    }
 }
@@ -3,7 +3,7 @@ use crate::util::{search, Dir, Grid, Point, Searchable};
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
+    fn solve_1(&self, input: &str) -> String {
         let city = City {
             map: input.parse().unwrap(),
             min_straight: 1,
@@ -13,7 +13,7 @@ impl crate::Solution for Solution {
         best.total_cost.to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
+    fn solve_2(&self, input: &str) -> String {
         let city = City {
             map: input.parse().unwrap(),
             min_straight: 4,
@@ -156,3 +156,149 @@ impl Searchable for City {
         true
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::{bidirectional_search, depth_limited_search, ida_star, search_with_budget};
+
+    const EX: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+    // (pos, dir of the last move); no dir means the start or the goal
+    type Key = (Point, Option<Dir>);
+
+    fn forward(city: &City, (p, dir): &Key) -> Vec<(Key, i32)> {
+        let goal = city.goal_point();
+        let turns = match dir {
+            Some(_) if *p == goal => return vec![((goal, None), 0)],
+            Some(d) => vec![d.turn_left(), d.turn_right()],
+            None => vec![Dir::E, Dir::S],
+        };
+        let mut res = Vec::new();
+        for d in turns {
+            let mut cost = 0;
+            let mut pos = *p;
+            for k in 1..=city.max_straight {
+                pos = pos + d.diff();
+                let Some(c) = city.map.get(pos) else { break };
+                cost += i32::from(c.0);
+                if k >= city.min_straight {
+                    res.push(((pos, Some(d)), cost));
+                }
+            }
+        }
+        res
+    }
+
+    fn reverse(city: &City, (p, dir): &Key) -> Vec<(Key, i32)> {
+        let Some(d) = dir else {
+            return Dir::values().map(|d| ((*p, Some(d)), 0)).to_vec();
+        };
+        let mut res = Vec::new();
+        let mut cost = 0;
+        let mut pos = *p;
+        for k in 1..=city.max_straight {
+            let Some(c) = city.map.get(pos) else { break };
+            cost += i32::from(c.0);
+            pos = pos + d.opposite().diff();
+            if city.map.get(pos).is_none() {
+                break;
+            }
+            if k >= city.min_straight {
+                res.push(((pos, Some(d.turn_left())), cost));
+                res.push(((pos, Some(d.turn_right())), cost));
+                if pos == Point(0, 0) && matches!(d, Dir::E | Dir::S) {
+                    res.push(((pos, None), cost));
+                }
+            }
+        }
+        res
+    }
+
+    fn check(min_straight: u8, max_straight: u8) {
+        let city = City {
+            map: EX.parse().unwrap(),
+            min_straight,
+            max_straight,
+        };
+        let (best, _) = search(&city).unwrap();
+        let (path, cost) = bidirectional_search(
+            (Point(0, 0), None),
+            (city.goal_point(), None),
+            |k| forward(&city, k),
+            |k| reverse(&city, k),
+        )
+        .unwrap();
+        assert_eq!(best.total_cost, cost);
+        assert_eq!(Some(&(Point(0, 0), None)), path.first());
+        assert_eq!(Some(&(city.goal_point(), None)), path.last());
+        let path_cost: i32 = path
+            .windows(2)
+            .map(|w| {
+                forward(&city, &w[0])
+                    .into_iter()
+                    .find(|(k, _)| k == &w[1])
+                    .unwrap()
+                    .1
+            })
+            .sum();
+        assert_eq!(cost, path_cost);
+    }
+
+    #[test]
+    fn bidirectional_1() {
+        check(1, 3);
+    }
+
+    #[test]
+    fn bidirectional_2() {
+        check(4, 10);
+    }
+
+    const SMALL: &str = "24134
+32154
+32552
+34465
+45466";
+
+    fn small_city(min_straight: u8, max_straight: u8) -> City {
+        City {
+            map: SMALL.parse().unwrap(),
+            min_straight,
+            max_straight,
+        }
+    }
+
+    #[test]
+    fn bounded_memory() {
+        for (min_straight, max_straight) in [(1, 3), (2, 4)] {
+            let city = small_city(min_straight, max_straight);
+            let (best, _) = search(&city).unwrap();
+            let (ida, _) = ida_star(&city).unwrap();
+            assert_eq!(best.total_cost, ida.total_cost);
+            let (budget, _) = search_with_budget(&city, 10).unwrap();
+            assert_eq!(best.total_cost, budget.total_cost);
+        }
+    }
+
+    #[test]
+    fn depth_limited() {
+        let city = small_city(1, 3);
+        assert!(depth_limited_search(&city, 1).is_none());
+        let (best, _) = search(&city).unwrap();
+        let (dl, _) = depth_limited_search(&city, 10).unwrap();
+        assert!(dl.total_cost >= best.total_cost);
+    }
+}
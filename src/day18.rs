@@ -1,22 +1,25 @@
-use crate::util::{parse_lines, Dir, Point};
+use crate::util::{boundary_points, interior_points, parse_lines, Dir, Parse, ParseError, Point};
 use itertools::Itertools;
-use std::str::FromStr;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        find_area(parse_lines(&input)).to_string()
+    fn solve_1(&self, input: &str) -> String {
+        find_area(parse_lines(input)).to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        find_area(parse_lines(&input).update(Instruction::fix)).to_string()
+    fn solve_2(&self, input: &str) -> String {
+        find_area(parse_lines(input).update(Instruction::fix)).to_string()
     }
 }
 
+#[derive(Parse)]
+#[parse("{dir} {dist} (#{color})")]
 struct Instruction {
+    #[parse(with = parse_dir)]
     dir: Dir,
     dist: i32,
+    #[parse(with = parse_color)]
     color: (i32, Dir),
 }
 
@@ -27,58 +30,80 @@ impl Instruction {
     }
 }
 
-impl FromStr for Instruction {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split_ascii_whitespace();
-        let dir_str = parts.next().unwrap();
-        let dir = match dir_str {
-            "U" => Dir::N,
-            "D" => Dir::S,
-            "L" => Dir::W,
-            "R" => Dir::E,
-            _ => panic!("what is this {dir_str}"),
-        };
-        let dist = parts.next().unwrap().parse().unwrap();
-        let color_str = parts.next().unwrap().trim_matches(&['#', '(', ')'][..]);
-        let color_dist = i32::from_str_radix(&color_str[0..5], 16).unwrap();
-        let color_dir = match &color_str[5..6] {
-            "0" => Dir::E,
-            "1" => Dir::S,
-            "2" => Dir::W,
-            "3" => Dir::N,
-            _ => panic!("srsly what is this"),
-        };
-        Ok(Instruction {
-            dir,
-            dist,
-            color: (color_dist, color_dir),
-        })
+fn parse_dir(s: &str) -> Result<Dir, ParseError> {
+    match s {
+        "U" => Ok(Dir::N),
+        "D" => Ok(Dir::S),
+        "L" => Ok(Dir::W),
+        "R" => Ok(Dir::E),
+        _ => Err(ParseError::at(0, format!("unknown direction {s:?}"))),
     }
 }
 
-struct Segment {
-    start: Point<i64>,
-    end: Point<i64>,
+fn parse_color(s: &str) -> Result<(i32, Dir), ParseError> {
+    if s.len() != 6 || !s.is_ascii() {
+        return Err(ParseError::at(
+            0,
+            format!("expected 6 hex digits, got {s:?}"),
+        ));
+    }
+    let dist = i32::from_str_radix(&s[0..5], 16)
+        .map_err(|e| ParseError::at(0, format!("{:?} is not hex: {e}", &s[0..5])))?;
+    let dir = match &s[5..6] {
+        "0" => Dir::E,
+        "1" => Dir::S,
+        "2" => Dir::W,
+        "3" => Dir::N,
+        d => return Err(ParseError::at(5, format!("unknown direction {d:?}"))),
+    };
+    Ok((dist, dir))
 }
 
 fn find_area(instructions: impl Iterator<Item = Instruction>) -> i64 {
-    let area = instructions
-        .scan(Point::zero(), |start, instr| {
-            let end = *start + instr.dir.diff().into() * i64::from(instr.dist);
-            let seg = Segment { start: *start, end };
-            *start = end;
-            Some(seg)
+    let vertices: Vec<Point<i64>> = instructions
+        .scan(Point::zero(), |pos, instr| {
+            *pos = *pos + instr.dir.diff().into() * i64::from(instr.dist);
+            Some(*pos)
         })
-        .map(|seg| {
-            if seg.start.0 == seg.end.0 {
-                (seg.start.1 - seg.end.1) * -seg.start.0 + (seg.start.1 - seg.end.1).abs()
-            } else {
-                (seg.start.0 - seg.end.0) * seg.start.1 + (seg.start.0 - seg.end.0).abs()
-            }
-        })
-        .sum::<i64>();
+        .collect();
+
+    // the trench is the boundary, and each cubic meter is a lattice point
+    interior_points(&vertices) + boundary_points(&vertices)
+}
 
-    area / 2 + 1
+#[cfg(test)]
+mod test {
+    use super::Instruction;
+    use crate::Solution as _;
+
+    const EX: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn ex() {
+        assert_eq!("62", super::Solution.solve_1(EX));
+        assert_eq!("952408144115", super::Solution.solve_2(EX));
+    }
+
+    #[test]
+    fn bad_colors() {
+        let err = "R 6 (#70c71)".parse::<Instruction>().err().unwrap();
+        assert!(err.to_string().contains("expected 6 hex digits"), "{err}");
+        let err = "R 6 (#70c714)".parse::<Instruction>().err().unwrap();
+        assert!(err.to_string().contains("unknown direction \"4\""), "{err}");
+        let err = "R 6 (#1234é)".parse::<Instruction>().err().unwrap();
+        assert!(err.to_string().contains("expected 6 hex digits"), "{err}");
+    }
 }
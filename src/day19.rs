@@ -1,225 +1,547 @@
-use crate::util::parse_lines;
-use once_cell::sync::Lazy;
-use regex::Regex;
+use crate::util::{parse_at, parse_lines, sections, Dot, Interner, NBox, Parse, ParseError};
 use std::collections::HashMap;
-use std::ops::Range;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let (workflows, parts) = parse_input(&input);
+    fn solve_1(&self, input: &str) -> String {
+        let (workflows, parts) = parse_input(input);
+        let tree = compile(&workflows);
 
+        let ratings: Vec<_> = parts.iter().map(Part::ratings).collect();
         parts
-            .into_iter()
-            .filter(|part| {
-                let mut wf = &workflows["in"];
-                loop {
-                    let dest = wf.process(part);
-                    match dest {
-                        "A" => return true,
-                        "R" => return false,
-                        _ => wf = &workflows[dest],
-                    }
-                }
-            })
-            .map(|part| part.total_ratings())
-            .sum::<i32>()
+            .iter()
+            .zip(tree.evaluate(&ratings))
+            .filter(|(_, accepted)| *accepted)
+            .map(|(part, _)| part.total_ratings())
+            .sum::<i64>()
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let (workflows, _) = parse_input(&input);
-        let in_play = [
-            1..4001, // x
-            1..4001, // m
-            1..4001, // a
-            1..4001, // s
-        ];
-        accept_count(&workflows, "in", in_play).to_string()
+    fn solve_2(&self, input: &str) -> String {
+        let (workflows, _) = parse_input(input);
+        let tree = compile(&workflows);
+        tree.accepted_boxes()
+            .iter()
+            .map(NBox::volume)
+            .sum::<i64>()
+            .to_string()
+    }
+
+    fn dot(&self, input: &str) -> Option<Dot> {
+        let (workflows, _) = parse_input(input);
+        let mut dot = Dot::digraph("workflows");
+        dot.node("A", &[("color", "green")]);
+        dot.node("R", &[("color", "red")]);
+        let dest_name = |dest| match dest {
+            Dest::Accept => "A",
+            Dest::Reject => "R",
+            Dest::Workflow(id) => workflows.names.name(id),
+        };
+        for (id, wf) in workflows.flows.iter().enumerate() {
+            let Some(wf) = wf else { continue };
+            let name = workflows.names.name(id);
+            dot.node(name, &[("shape", "box")]);
+            for cond in &wf.conditions {
+                let op = match cond.op {
+                    Op::Greater => '>',
+                    Op::Less => '<',
+                };
+                let label = format!("{}{op}{}", AXES[cond.axis], cond.test);
+                dot.edge(name, dest_name(cond.dest), &[("label", &label)]);
+            }
+            dot.edge(name, dest_name(wf.final_dest), &[]);
+        }
+        Some(dot)
+    }
+}
+
+/**
+The names of the ratings, in the order they are stored
+*/
+const AXES: [char; 4] = ['x', 'm', 'a', 's'];
+
+fn compile(workflows: &Workflows) -> DecisionTree {
+    let domain = NBox::new([1; 4], [4001; 4]);
+    let compiled = DecisionTree::compile(workflows, domain).unwrap_or_else(|e| panic!("{e}"));
+    if !compiled.unreachable.is_empty() {
+        log::info!("unreachable workflows: {:?}", compiled.unreachable);
     }
+    log::info!("compiled to {} nodes", compiled.tree.nodes.len());
+    compiled.tree
 }
 
-fn parse_input(input: &str) -> (HashMap<String, Workflow>, Vec<Part>) {
-    let (workflow_strs, part_strs) = input.split_once("\n\n").unwrap();
-    let workflows = parse_lines::<Workflow>(workflow_strs)
-        .map(|wf| (wf.name.clone(), wf))
-        .collect();
+fn parse_input(input: &str) -> (Workflows<'_>, Vec<Part>) {
+    let mut sections = sections(input);
+    let workflow_strs = sections.next().unwrap();
+    let part_strs = sections.next().unwrap();
+    let workflows = Workflows::parse(workflow_strs);
     let parts = parse_lines(part_strs).collect();
     (workflows, parts)
 }
 
+/**
+Every workflow, indexed by its interned name, and the index of `in`
+*/
+struct Workflows<'a> {
+    names: Interner<'a>,
+    flows: Vec<Option<Workflow>>,
+    start: usize,
+}
+
+impl Workflows<'_> {
+    fn parse(s: &str) -> Workflows<'_> {
+        let mut names = Interner::new();
+        let mut flows = Vec::new();
+        for line in s.lines() {
+            let (id, wf) = Workflow::parse(line, &mut names);
+            flows.resize_with(names.len(), || None);
+            flows[id] = Some(wf);
+        }
+        flows.resize_with(names.len(), || None);
+        let start = names.id("in").expect("no in workflow");
+        Workflows {
+            names,
+            flows,
+            start,
+        }
+    }
+}
+
+#[derive(Parse)]
+#[parse("{{x={x},m={m},a={a},s={s}}}")]
 struct Part {
-    values: HashMap<char, i32>,
+    x: i64,
+    m: i64,
+    a: i64,
+    s: i64,
 }
 
 impl Part {
-    fn total_ratings(&self) -> i32 {
-        self.values.values().sum::<i32>()
+    /**
+    The ratings in the order of [`AXES`]
+    */
+    fn ratings(&self) -> [i64; 4] {
+        [self.x, self.m, self.a, self.s]
+    }
+
+    fn total_ratings(&self) -> i64 {
+        self.ratings().iter().sum()
     }
 }
 
-impl FromStr for Part {
-    type Err = ();
+#[derive(Copy, Clone)]
+enum Dest {
+    Accept,
+    Reject,
+    Workflow(usize),
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let values = s
-            .trim_matches(&['{', '}'][..])
-            .split(',')
-            .map(|kv| {
-                let (k, v) = kv.split_once('=').unwrap();
-                (k.chars().next().unwrap(), v.parse().unwrap())
-            })
-            .collect();
-        Ok(Part { values })
+impl Dest {
+    fn parse<'a>(s: &'a str, names: &mut Interner<'a>) -> Dest {
+        match s {
+            "A" => Dest::Accept,
+            "R" => Dest::Reject,
+            name => Dest::Workflow(names.intern(name)),
+        }
     }
 }
 
 struct Workflow {
-    name: String,
     conditions: Vec<Condition>,
-    final_dest: String,
+    final_dest: Dest,
 }
 
-impl Workflow {
-    fn process(&self, part: &Part) -> &str {
-        for cond in &self.conditions {
-            if cond.accepts(part) {
-                return &cond.dest;
-            }
-        }
-        &self.final_dest
-    }
+#[derive(Parse)]
+#[parse("{name}{{{rules}}}")]
+struct WorkflowText<'a> {
+    name: &'a str,
+    rules: &'a str,
 }
 
-impl FromStr for Workflow {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        static COND_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w)([><])(\d+):(\w+)").unwrap());
+#[derive(Parse)]
+#[parse("{comparison}:{dest}")]
+struct RuleText<'a> {
+    comparison: Comparison,
+    dest: &'a str,
+}
 
-        let (name_str, conditions_str) = s.trim_end_matches('}').split_once('{').unwrap();
-        let name = name_str.to_string();
-        let conditions_raw: Vec<_> = conditions_str.split(',').collect();
-        let conditions = conditions_raw[..conditions_raw.len() - 1]
-            .iter()
-            .map(|cond_str| {
-                let m = COND_RE.captures(cond_str).unwrap();
+impl Workflow {
+    /**
+    Parses a workflow, interning its name and the names of the workflows it sends to. Returns the id
+    of its name.
+    */
+    fn parse<'a>(s: &'a str, names: &mut Interner<'a>) -> (usize, Workflow) {
+        let text = WorkflowText::parse(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+        let id = names.intern(text.name);
+        let mut rules: Vec<_> = text.rules.split(',').collect();
+        let final_dest = Dest::parse(rules.pop().unwrap(), names);
+        let conditions = rules
+            .into_iter()
+            .map(|rule| {
+                let rule = RuleText::parse(rule).unwrap_or_else(|e| panic!("{rule}: {e}"));
                 Condition {
-                    var: m[1].chars().next().unwrap(),
-                    op: match &m[2] {
-                        ">" => Op::Greater,
-                        "<" => Op::Less,
-                        _ => unreachable!(),
-                    },
-                    test: m[3].parse().unwrap(),
-                    dest: m[4].to_string(),
+                    axis: rule.comparison.axis,
+                    op: rule.comparison.op,
+                    test: rule.comparison.test,
+                    dest: Dest::parse(rule.dest, names),
                 }
             })
             .collect();
-        let final_dest = conditions_raw[conditions_raw.len() - 1].to_string();
-        Ok(Workflow {
-            name,
-            conditions,
-            final_dest,
-        })
+        (
+            id,
+            Workflow {
+                conditions,
+                final_dest,
+            },
+        )
     }
 }
 
-struct Condition {
-    var: char,
+/**
+A rating compared with a number, like `a<2006`
+*/
+struct Comparison {
+    axis: usize,
     op: Op,
-    test: i32,
-    dest: String,
+    test: i64,
 }
 
-impl Condition {
-    fn accepts(&self, part: &Part) -> bool {
-        self.op.compare(part.values[&self.var], self.test)
+impl FromStr for Comparison {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let at = s
+            .find(['<', '>'])
+            .ok_or_else(|| ParseError::at(0, "expected < or >"))?;
+        let axis = AXES
+            .iter()
+            .position(|a| s[..at].chars().eq([*a]))
+            .ok_or_else(|| ParseError::at(0, format!("unknown rating {:?}", &s[..at])))?;
+        let op = if s.as_bytes()[at] == b'>' {
+            Op::Greater
+        } else {
+            Op::Less
+        };
+        let test = parse_at(&s[at + 1..], at + 1)?;
+        Ok(Comparison { axis, op, test })
     }
 }
 
+struct Condition {
+    axis: usize,
+    op: Op,
+    test: i64,
+    dest: Dest,
+}
+
 enum Op {
     Greater,
     Less,
 }
 
-impl Op {
-    fn compare(&self, value: i32, test: i32) -> bool {
+const ACCEPT: usize = 0;
+const REJECT: usize = 1;
+
+/**
+A node of a compiled decision tree. Nodes [`ACCEPT`] and [`REJECT`] are always the leaves.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Node {
+    Accept,
+    Reject,
+    /**
+    Parts rated below `at` on `axis` go to `below`, the rest to `above`
+    */
+    Test {
+        axis: usize,
+        at: i64,
+        below: usize,
+        above: usize,
+    },
+}
+
+/**
+The workflows compiled into a single tree of rating tests. Tests whose outcome is already decided by
+the tests above them are pruned, and identical subtrees are shared, so each node is distinct.
+*/
+struct DecisionTree {
+    nodes: Vec<Node>,
+    root: usize,
+    /**
+    The ratings the tree was compiled for. Parts rated outside of it may be misjudged.
+    */
+    domain: NBox<4>,
+}
+
+struct Compiled<'a> {
+    tree: DecisionTree,
+    /**
+    Workflows that no part in the domain can reach
+    */
+    unreachable: Vec<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum CompileError<'a> {
+    /**
+    Workflows that send some parts around in a loop, starting and ending with the same one
+    */
+    Cycle(Vec<&'a str>),
+    /**
+    A workflow that some parts are sent to, but that doesn't exist
+    */
+    Undefined(&'a str),
+}
+
+impl Display for CompileError<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Op::Greater => value > test,
-            Op::Less => value < test,
+            CompileError::Cycle(names) => write!(f, "workflows loop: {}", names.join(" -> ")),
+            CompileError::Undefined(name) => write!(f, "workflow {name} is not defined"),
         }
     }
 }
 
-fn accept_count(
-    workflows: &HashMap<String, Workflow>,
-    name: &str,
-    mut in_play: [Range<i64>; 4],
-) -> i64 {
-    let wf = &workflows[name];
-    let mut total = 0;
+impl DecisionTree {
+    fn compile<'a>(
+        workflows: &Workflows<'a>,
+        domain: NBox<4>,
+    ) -> Result<Compiled<'a>, CompileError<'a>> {
+        let mut compiler = Compiler {
+            workflows,
+            nodes: vec![Node::Accept, Node::Reject],
+            ids: HashMap::from([(Node::Accept, ACCEPT), (Node::Reject, REJECT)]),
+            reached: vec![false; workflows.flows.len()],
+            stack: Vec::new(),
+        };
+        let root = compiler.dest(Dest::Workflow(workflows.start), domain)?;
+        let unreachable = (0..workflows.flows.len())
+            .filter(|id| workflows.flows[*id].is_some() && !compiler.reached[*id])
+            .map(|id| workflows.names.name(id))
+            .collect();
+        Ok(Compiled {
+            tree: DecisionTree {
+                nodes: compiler.nodes,
+                root,
+                domain,
+            },
+            unreachable,
+        })
+    }
 
-    for cond in &wf.conditions {
-        let mut matches = in_play.clone();
-        let ipi = ['x', 'm', 'a', 's']
-            .iter()
-            .position(|c| c == &cond.var)
-            .unwrap();
-        matches[ipi] = match cond.op {
-            Op::Greater => cut_top(&mut in_play[ipi], cond.test),
-            Op::Less => cut_bottom(&mut in_play[ipi], cond.test),
+    /**
+    Whether each part is accepted, found by sending all of them down the tree together
+    */
+    fn evaluate(&self, parts: &[[i64; 4]]) -> Vec<bool> {
+        let mut res = vec![false; parts.len()];
+        let mut stack = vec![(self.root, (0..parts.len()).collect::<Vec<_>>())];
+        while let Some((node, indexes)) = stack.pop() {
+            match self.nodes[node] {
+                Node::Accept => {
+                    for i in indexes {
+                        res[i] = true;
+                    }
+                }
+                Node::Reject => {}
+                Node::Test {
+                    axis,
+                    at,
+                    below,
+                    above,
+                } => {
+                    let (lower, upper) = indexes.into_iter().partition(|i| parts[*i][axis] < at);
+                    stack.push((below, lower));
+                    stack.push((above, upper));
+                }
+            }
+        }
+        res
+    }
+
+    /**
+    Disjoint boxes of ratings that together are exactly the accepted part of the domain
+    */
+    fn accepted_boxes(&self) -> Vec<NBox<4>> {
+        let mut res = Vec::new();
+        let mut stack = vec![(self.root, self.domain)];
+        while let Some((node, in_play)) = stack.pop() {
+            match self.nodes[node] {
+                Node::Accept => res.push(in_play),
+                Node::Reject => {}
+                Node::Test {
+                    axis,
+                    at,
+                    below,
+                    above,
+                } => {
+                    let (lower, upper) = in_play.split_at(axis, at);
+                    stack.push((below, lower));
+                    stack.push((above, upper));
+                }
+            }
+        }
+        res
+    }
+}
+
+struct Compiler<'w, 'a> {
+    workflows: &'w Workflows<'a>,
+    nodes: Vec<Node>,
+    ids: HashMap<Node, usize>,
+    reached: Vec<bool>,
+    /**
+    The workflows being compiled, to spot ones that send parts back to themselves
+    */
+    stack: Vec<usize>,
+}
+
+impl<'a> Compiler<'_, 'a> {
+    fn node(&mut self, node: Node) -> usize {
+        if let Node::Test { below, above, .. } = node {
+            if below == above {
+                return below;
+            }
+        }
+        *self.ids.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        })
+    }
+
+    fn dest(&mut self, dest: Dest, in_play: NBox<4>) -> Result<usize, CompileError<'a>> {
+        match dest {
+            Dest::Accept => Ok(ACCEPT),
+            Dest::Reject => Ok(REJECT),
+            Dest::Workflow(id) => {
+                let name = self.workflows.names.name(id);
+                if let Some(i) = self.stack.iter().position(|s| *s == id) {
+                    let mut cycle: Vec<_> = self.stack[i..]
+                        .iter()
+                        .map(|s| self.workflows.names.name(*s))
+                        .collect();
+                    cycle.push(name);
+                    return Err(CompileError::Cycle(cycle));
+                }
+                let workflows = self.workflows;
+                let wf = workflows.flows[id]
+                    .as_ref()
+                    .ok_or(CompileError::Undefined(name))?;
+                self.reached[id] = true;
+                self.stack.push(id);
+                let res = self.conditions(&wf.conditions, wf.final_dest, in_play);
+                self.stack.pop();
+                res
+            }
+        }
+    }
+
+    /**
+    Compiles the rest of a workflow, for the parts in `in_play`, which is never empty
+    */
+    fn conditions(
+        &mut self,
+        conditions: &[Condition],
+        final_dest: Dest,
+        in_play: NBox<4>,
+    ) -> Result<usize, CompileError<'a>> {
+        let Some((cond, rest)) = conditions.split_first() else {
+            return self.dest(final_dest, in_play);
+        };
+        let at = match cond.op {
+            Op::Less => cond.test,
+            Op::Greater => cond.test + 1,
+        };
+        let (lower, upper) = in_play.split_at(cond.axis, at);
+        let (matched, unmatched) = match cond.op {
+            Op::Less => (lower, upper),
+            Op::Greater => (upper, lower),
         };
-        total += match cond.dest.as_str() {
-            "A" => count(&matches),
-            "R" => 0,
-            dep => accept_count(workflows, dep, matches),
+        if matched.is_empty() {
+            return self.conditions(rest, final_dest, unmatched);
         }
+        let matched_node = self.dest(cond.dest, matched)?;
+        if unmatched.is_empty() {
+            return Ok(matched_node);
+        }
+        let unmatched_node = self.conditions(rest, final_dest, unmatched)?;
+        let (below, above) = match cond.op {
+            Op::Less => (matched_node, unmatched_node),
+            Op::Greater => (unmatched_node, matched_node),
+        };
+        Ok(self.node(Node::Test {
+            axis: cond.axis,
+            at,
+            below,
+            above,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CompileError, Compiled, DecisionTree, Workflows, ACCEPT};
+    use crate::util::NBox;
+    use crate::Solution as _;
+
+    const EX: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    #[test]
+    fn ex() {
+        assert_eq!("19114", super::Solution.solve_1(EX));
+        assert_eq!("167409079868000", super::Solution.solve_2(EX));
+    }
+
+    fn compile(workflows: &str) -> Result<Compiled<'_>, CompileError<'_>> {
+        DecisionTree::compile(&Workflows::parse(workflows), NBox::new([1; 4], [4001; 4]))
+    }
+
+    #[test]
+    fn pruned() {
+        // every part ends up accepted: a's test can never pass, and b accepts either way
+        let compiled = compile("in{x<10:a,b}\na{x>20:R,A}\nb{m<5:A,A}\nc{x<1:A,R}").unwrap();
+        assert_eq!(ACCEPT, compiled.tree.root);
+        assert_eq!(vec!["c"], compiled.unreachable);
+        assert_eq!(vec![compiled.tree.domain], compiled.tree.accepted_boxes());
+
+        // both branches lead to the same test, which is only built once
+        let compiled = compile("in{x<10:a,b}\na{m>5:A,R}\nb{m>5:A,R}").unwrap();
+        assert_eq!(3, compiled.tree.nodes.len());
+        assert_eq!(
+            vec![true, false],
+            compiled.tree.evaluate(&[[1, 6, 1, 1], [11, 5, 1, 1]])
+        );
     }
 
-    total += match wf.final_dest.as_str() {
-        "A" => count(&in_play),
-        "R" => 0,
-        final_dest => accept_count(workflows, final_dest, in_play),
-    };
-
-    log::debug!("{name} -> {total}");
-    total
-}
-
-fn count(ranges: &[Range<i64>]) -> i64 {
-    ranges.iter().map(|r| r.end - r.start).product::<i64>()
-}
-
-// cuts the top off a range, returning the range that it cut
-fn cut_top(range: &mut Range<i64>, v: i32) -> Range<i64> {
-    let v = i64::from(v) + 1;
-    if range.start > v {
-        let old = range.clone();
-        range.end = range.start;
-        old
-    } else if range.end > v {
-        let old_end = range.end;
-        range.end = v;
-        v..old_end
-    } else {
-        v..v
-    }
-}
-
-fn cut_bottom(range: &mut Range<i64>, v: i32) -> Range<i64> {
-    let v = i64::from(v);
-    if range.end < v {
-        let old = range.clone();
-        range.start = range.end;
-        old
-    } else if range.start < v {
-        let old_start = range.start;
-        range.start = v;
-        old_start..v
-    } else {
-        v..v
+    #[test]
+    fn compile_errors() {
+        assert_eq!(
+            Some(CompileError::Cycle(vec!["in", "a", "in"])),
+            compile("in{x<10:a,A}\na{m<5:in,R}").err()
+        );
+        assert_eq!(
+            Some(CompileError::Undefined("b")),
+            compile("in{x<10:a,A}\na{m<5:b,R}").err()
+        );
+        // a loop that no part can take is fine
+        assert!(compile("in{x<10:a,A}\na{x>20:in,R}").is_ok());
     }
 }
@@ -1,11 +1,10 @@
-use crate::util::parse_lines;
-use std::str::FromStr;
+use crate::util::{key_values, offset_of, parse_at, parse_field, parse_lines, Parse, ParseError};
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        parse_lines(&input)
+    fn solve_1(&self, input: &str) -> String {
+        parse_lines(input)
             .filter(|game: &Game| {
                 game.picks
                     .iter()
@@ -16,8 +15,8 @@ impl crate::Solution for Solution {
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        parse_lines(&input)
+    fn solve_2(&self, input: &str) -> String {
+        parse_lines(input)
             .map(|game: Game| {
                 game.picks
                     .into_iter()
@@ -29,8 +28,11 @@ impl crate::Solution for Solution {
     }
 }
 
+#[derive(Parse)]
+#[parse("Game {id}: {picks}")]
 struct Game {
     id: i32,
+    #[parse(with = parse_picks)]
     picks: Vec<Pick>,
 }
 
@@ -55,39 +57,52 @@ impl Pick {
     }
 }
 
-impl FromStr for Game {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (head, tail) = s.split_once(':').ok_or_else(|| s.to_string())?;
+fn parse_picks(s: &str) -> Result<Vec<Pick>, ParseError> {
+    s.split(';')
+        .map(|pick_str| {
+            parse_field(offset_of(s, pick_str), || {
+                let mut pick = Pick::default();
+                for (n_str, color) in key_values(pick_str, ",", " ")? {
+                    let n = parse_at(n_str, offset_of(pick_str, n_str))?;
+                    match color {
+                        "red" => pick.red = n,
+                        "green" => pick.green = n,
+                        "blue" => pick.blue = n,
+                        _ => {
+                            let at = offset_of(pick_str, color);
+                            return Err(ParseError::at(at, format!("unknown color {color}")));
+                        }
+                    }
+                }
+                Ok(pick)
+            })
+        })
+        .collect()
+}
 
-        let id: i32 = head
-            .chars()
-            .skip(5)
-            .collect::<String>()
-            .parse()
-            .map_err(|_| s.to_string())?;
+#[cfg(test)]
+mod test {
+    use crate::Solution as _;
 
-        let mut picks = Vec::new();
-        for pick_str in tail.split(';') {
-            let mut pick = Pick::default();
-            for color_n_str in pick_str.split(',') {
-                let (n_str, color) = color_n_str
-                    .trim()
-                    .split_once(' ')
-                    .ok_or_else(|| color_n_str.to_string())?;
-                let n: i32 = n_str.parse().map_err(|_| s.to_string())?;
+    const EX: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
 
-                match color {
-                    "red" => pick.red = n,
-                    "green" => pick.green = n,
-                    "blue" => pick.blue = n,
-                    _ => return Err(format!("unknown color {color}")),
-                }
-            }
-            picks.push(pick);
-        }
+    #[test]
+    fn ex() {
+        assert_eq!("8", super::Solution.solve_1(EX));
+        assert_eq!("2286", super::Solution.solve_2(EX));
+    }
 
-        Ok(Game { id, picks })
+    #[test]
+    fn parse_error() {
+        let err = "Game 1: 3 blue; 4 rde"
+            .parse::<super::Game>()
+            .err()
+            .unwrap();
+        assert_eq!(19, err.column);
+        assert_eq!(Some("picks"), err.field);
     }
 }
@@ -1,160 +1,554 @@
-use crate::util::parse_lines;
-use std::collections::HashMap;
-use std::str::FromStr;
+use crate::util::{crt, write_vcd, Dot, Interner, Parse};
+use itertools::Itertools;
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Write};
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let mut modules = parse_input(&input);
-        let mut low = 0;
-        let mut high = 0;
+    fn solve_1(&self, input: &str) -> String {
+        let circuit = Circuit::parse(input);
+        let mut state = circuit.initial_state();
+        let mut low = 0u64;
+        let mut high = 0u64;
         for _ in 0..1000 {
-            let (l, h) = push_button(&mut modules);
-            low += l;
-            high += h;
+            circuit.press(&mut state, |pulse| {
+                if pulse.high {
+                    high += 1;
+                } else {
+                    low += 1;
+                }
+            });
         }
         (low * high).to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let modules = parse_input(&input);
-        let broad_dests = &modules["broadcaster"].dests;
-        let mut cycles = Vec::new();
-        for dest in broad_dests {
-            let mut c = 0i64;
-            let mut place = 0u32;
-            let mut current = dest;
-            loop {
-                log::debug!("looking at {current}: c {c} place {place}");
-                let mo_dests = &modules[current].dests;
-                if mo_dests.iter().any(|d| modules[d].typ.is_conjuction()) {
-                    log::debug!("{current} connects");
-                    c += 2i64.pow(place);
-                }
-                place += 1;
-                let next = mo_dests
-                    .iter()
-                    .find(|d| modules[d.as_str()].typ.is_flip_flip());
-                if let Some(next) = next {
-                    current = next;
-                } else {
-                    break;
-                }
+    fn solve_2(&self, input: &str) -> String {
+        let circuit = Circuit::parse(input);
+        let rx = circuit.names.id("rx").expect("no rx module");
+        fewest_presses(&circuit, rx, MAX_PRESSES)
+            .expect("rx never gets a low pulse")
+            .to_string()
+    }
+
+    /**
+    Every pulse of the 1000 presses of part 1, or if modules are named, only the pulses they send or
+    receive
+    */
+    fn vcd(&self, input: &str, signals: &[&str]) -> Option<String> {
+        let circuit = Circuit::parse(input);
+        let mut trace = Trace::record(&circuit, 1000);
+        if !signals.is_empty() {
+            let modules: Vec<_> = signals
+                .iter()
+                .map(|name| {
+                    circuit
+                        .names
+                        .id(name)
+                        .unwrap_or_else(|| panic!("no module {name}"))
+                })
+                .collect();
+            trace = trace.involving(&modules);
+        }
+        let mut out = Vec::new();
+        trace.write_vcd(&circuit, &mut out).unwrap();
+        Some(String::from_utf8(out).unwrap())
+    }
+
+    fn dot(&self, input: &str) -> Option<Dot> {
+        let circuit = Circuit::parse(input);
+        let mut dot = Dot::digraph("modules");
+        for (id, name) in circuit.names.names().iter().enumerate() {
+            let (label, shape) = match circuit.kinds[id] {
+                Kind::FlipFlop => (format!("%{name}"), "box"),
+                Kind::Conjunction => (format!("&{name}"), "diamond"),
+                Kind::Broadcaster | Kind::Button => (name.to_string(), "doubleoctagon"),
+                Kind::Sink => (name.to_string(), "doublecircle"),
+            };
+            dot.node(name, &[("label", &label), ("shape", shape)]);
+            for (dest, _) in &circuit.dests[id] {
+                dot.edge(name, circuit.names.name(*dest), &[]);
             }
-            cycles.push(c);
         }
-        log::info!("{cycles:?}");
-        cycles.into_iter().product::<i64>().to_string()
+        Some(dot)
     }
 }
 
-fn parse_input(input: &str) -> HashMap<String, Module> {
-    let mut modules: HashMap<String, Module> = parse_lines(input)
-        .map(|mo: Module| (mo.name.clone(), mo))
-        .collect();
-    let mut inputs_for_conj = Vec::new();
-    for mo in modules.values() {
-        if mo.typ.is_conjuction() {
-            let inputs: HashMap<_, _> = modules
-                .values()
-                .filter(|it| it.dests.contains(&mo.name))
-                .map(|it| (it.name.clone(), false))
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Kind {
+    FlipFlop,
+    Conjunction,
+    Broadcaster,
+    Button,
+    /**
+    A module that is only ever sent to, like `rx`
+    */
+    Sink,
+}
+
+/**
+A line of the input, like `%a -> b, c`
+*/
+#[derive(Parse)]
+#[parse("{module} -> {dests}")]
+struct ModuleText<'a> {
+    module: &'a str,
+    dests: &'a str,
+}
+
+/**
+The modules, with names interned to indexes into the other fields
+*/
+struct Circuit<'a> {
+    names: Interner<'a>,
+    kinds: Vec<Kind>,
+    /**
+    Each destination of a module, with which of the destination's inputs the module is
+    */
+    dests: Vec<Vec<(usize, usize)>>,
+    inputs: Vec<Vec<usize>>,
+    button: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Pulse {
+    from: usize,
+    to: usize,
+    /**
+    Which of `to`'s inputs `from` is
+    */
+    slot: usize,
+    high: bool,
+}
+
+/**
+Whether each flip-flop is on, and the last pulse each conjunction got from each of its inputs
+*/
+struct State {
+    on: Vec<bool>,
+    memory: Vec<Vec<bool>>,
+    high_inputs: Vec<usize>,
+}
+
+impl<'a> Circuit<'a> {
+    fn parse(input: &'a str) -> Circuit<'a> {
+        let mut names = Interner::new();
+        let mut kinds = Vec::new();
+        let mut dest_ids = Vec::new();
+        let button = names.intern("button");
+        for line in input.lines() {
+            let text = ModuleText::parse(line).unwrap_or_else(|e| panic!("{line}: {e}"));
+            let (kind, name) = match text.module.as_bytes()[0] {
+                b'%' => (Kind::FlipFlop, &text.module[1..]),
+                b'&' => (Kind::Conjunction, &text.module[1..]),
+                _ => (Kind::Broadcaster, text.module),
+            };
+            let id = names.intern(name);
+            let ds: Vec<_> = text
+                .dests
+                .split(',')
+                .map(|d| names.intern(d.trim()))
                 .collect();
-            inputs_for_conj.push((mo.name.clone(), inputs));
+            kinds.resize(names.len(), Kind::Sink);
+            dest_ids.resize(names.len(), Vec::new());
+            kinds[id] = kind;
+            dest_ids[id] = ds;
+        }
+        kinds.resize(names.len(), Kind::Sink);
+        dest_ids.resize(names.len(), Vec::new());
+        kinds[button] = Kind::Button;
+        dest_ids[button] = vec![names.id("broadcaster").expect("no broadcaster")];
+
+        let mut inputs = vec![Vec::new(); names.len()];
+        let dests = dest_ids
+            .into_iter()
+            .enumerate()
+            .map(|(from, ds)| {
+                ds.into_iter()
+                    .map(|to| {
+                        inputs[to].push(from);
+                        (to, inputs[to].len() - 1)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Circuit {
+            names,
+            kinds,
+            dests,
+            inputs,
+            button,
         }
     }
-    for (name, inp) in inputs_for_conj {
-        let mo = modules.get_mut(&name).unwrap();
-        if let ModuleType::Conjunction { inputs } = &mut mo.typ {
-            *inputs = inp;
+
+    fn initial_state(&self) -> State {
+        State {
+            on: vec![false; self.names.len()],
+            memory: self.inputs.iter().map(|i| vec![false; i.len()]).collect(),
+            high_inputs: vec![0; self.names.len()],
         }
     }
-    modules
-}
 
-fn push_button(modules: &mut HashMap<String, Module>) -> (i32, i32) {
-    let mut low = 0;
-    let mut high = 0;
-    let mut q = vec![("broadcaster".to_string(), false, String::new())];
-    while let Some((name, pulse, from)) = q.pop() {
-        if pulse {
-            high += 1;
-        } else {
-            low += 1;
+    /**
+    Pushes the button and runs until every pulse has been delivered, calling `trace` with each pulse
+    as it arrives
+    */
+    fn press(&self, state: &mut State, mut trace: impl FnMut(&Pulse)) {
+        let (broadcaster, _) = self.dests[self.button][0];
+        let mut queue = VecDeque::from([Pulse {
+            from: self.button,
+            to: broadcaster,
+            slot: 0,
+            high: false,
+        }]);
+        while let Some(pulse) = queue.pop_front() {
+            log::trace!(
+                "{} -{}-> {}",
+                self.names.name(pulse.from),
+                if pulse.high { "high" } else { "low" },
+                self.names.name(pulse.to)
+            );
+            trace(&pulse);
+            let to = pulse.to;
+            let sent = match self.kinds[to] {
+                Kind::FlipFlop if pulse.high => None,
+                Kind::FlipFlop => {
+                    state.on[to] = !state.on[to];
+                    Some(state.on[to])
+                }
+                Kind::Conjunction => {
+                    let remembered = &mut state.memory[to][pulse.slot];
+                    if *remembered != pulse.high {
+                        *remembered = pulse.high;
+                        if pulse.high {
+                            state.high_inputs[to] += 1;
+                        } else {
+                            state.high_inputs[to] -= 1;
+                        }
+                    }
+                    Some(state.high_inputs[to] != self.inputs[to].len())
+                }
+                Kind::Broadcaster | Kind::Button => Some(pulse.high),
+                Kind::Sink => None,
+            };
+            if let Some(high) = sent {
+                queue.extend(self.dests[to].iter().map(|&(dest, slot)| Pulse {
+                    from: to,
+                    to: dest,
+                    slot,
+                    high,
+                }));
+            }
         }
-        let Some(mo) = modules.get_mut(&name) else {
-            continue;
-        };
-        if let Some(result) = mo.receive(pulse, &from) {
-            for dest in &mo.dests {
-                q.push((dest.clone(), result, name.clone()));
+    }
+
+    /**
+    Every module that can affect `output`, apart from the button and broadcaster
+    */
+    fn subcircuit(&self, output: usize) -> HashSet<usize> {
+        let mut seen = HashSet::from([output]);
+        let mut stack = vec![output];
+        while let Some(id) = stack.pop() {
+            for &input in &self.inputs[id] {
+                if !matches!(self.kinds[input], Kind::Button | Kind::Broadcaster)
+                    && seen.insert(input)
+                {
+                    stack.push(input);
+                }
             }
         }
+        seen
     }
-    (low, high)
 }
 
-struct Module {
-    name: String,
-    typ: ModuleType,
-    dests: Vec<String>,
+/**
+A pulse as recorded in a trace, with the button press it happened on (from 1) and its place among
+that press's pulses (from 0)
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct TracedPulse {
+    press: u64,
+    seq: usize,
+    from: usize,
+    to: usize,
+    high: bool,
 }
 
-enum ModuleType {
-    FlipFlop { state: bool },
-    Conjunction { inputs: HashMap<String, bool> },
-    Broadcaster,
+/**
+Every pulse sent over a run of button presses, in the order they were delivered
+*/
+struct Trace {
+    pulses: Vec<TracedPulse>,
 }
 
-impl Module {
-    fn receive(&mut self, pulse: bool, from: &str) -> Option<bool> {
-        match &mut self.typ {
-            ModuleType::FlipFlop { state } => {
-                if pulse {
-                    None
-                } else {
-                    *state = !*state;
-                    Some(*state)
-                }
-            }
-            ModuleType::Conjunction { inputs } => {
-                *inputs.get_mut(from).unwrap() = pulse;
-                Some(!inputs.values().all(|b| *b))
+impl Trace {
+    fn record(circuit: &Circuit, presses: u64) -> Trace {
+        let mut state = circuit.initial_state();
+        let mut pulses = Vec::new();
+        for press in 1..=presses {
+            let mut seq = 0;
+            circuit.press(&mut state, |pulse| {
+                pulses.push(TracedPulse {
+                    press,
+                    seq,
+                    from: pulse.from,
+                    to: pulse.to,
+                    high: pulse.high,
+                });
+                seq += 1;
+            });
+        }
+        Trace { pulses }
+    }
+
+    /**
+    The pulses sent or received by any of `modules`
+    */
+    fn involving(&self, modules: &[usize]) -> Trace {
+        let pulses = self
+            .pulses
+            .iter()
+            .filter(|p| modules.contains(&p.from) || modules.contains(&p.to))
+            .copied()
+            .collect();
+        Trace { pulses }
+    }
+
+    /**
+    Writes the output of each module that sends a pulse as a signal. A flip-flop's output is its
+    state. Each press takes as many time steps as the longest press has pulses, so every pulse gets
+    its own time.
+    */
+    fn write_vcd(&self, circuit: &Circuit, out: &mut impl Write) -> io::Result<()> {
+        let senders: Vec<_> = self
+            .pulses
+            .iter()
+            .map(|p| p.from)
+            .sorted()
+            .dedup()
+            .collect();
+        let names: Vec<_> = senders.iter().map(|&id| circuit.names.name(id)).collect();
+        let press_len = self
+            .pulses
+            .iter()
+            .map(|p| p.seq as u64 + 1)
+            .max()
+            .unwrap_or(1);
+        let changes = self.pulses.iter().map(|p| {
+            let time = (p.press - 1) * press_len + p.seq as u64;
+            (time, senders.binary_search(&p.from).unwrap(), p.high)
+        });
+        write_vcd(out, "circuit", &names, changes)
+    }
+}
+
+/**
+The most button presses simulated for part 2
+*/
+const MAX_PRESSES: u64 = 1 << 20;
+
+/**
+The fewest button presses that send a low pulse to `target`.
+
+When `target` is fed by a single conjunction whose inputs are driven by disjoint sub-circuits, each
+input's high pulses are timed until they repeat and the periods are lined up. This assumes the
+inputs' high pulses overlap whenever they land on the same press. Otherwise the whole circuit is
+simulated until it happens. Either way, gives up after `max_presses` presses.
+*/
+fn fewest_presses(circuit: &Circuit, target: usize, max_presses: u64) -> Option<u64> {
+    if let [feeder] = circuit.inputs[target][..] {
+        if circuit.kinds[feeder] == Kind::Conjunction {
+            let subcircuits: Vec<_> = circuit.inputs[feeder]
+                .iter()
+                .map(|&input| circuit.subcircuit(input))
+                .collect();
+            let disjoint = subcircuits
+                .iter()
+                .enumerate()
+                .all(|(i, a)| subcircuits[i + 1..].iter().all(|b| a.is_disjoint(b)));
+            if disjoint {
+                return periodic_presses(circuit, feeder, max_presses);
             }
-            ModuleType::Broadcaster => Some(pulse),
+            log::info!("inputs of {} share modules", circuit.names.name(feeder));
         }
     }
+    let mut state = circuit.initial_state();
+    (1..=max_presses).find(|_| {
+        let mut done = false;
+        circuit.press(&mut state, |pulse| {
+            done |= pulse.to == target && !pulse.high;
+        });
+        done
+    })
 }
 
-impl FromStr for Module {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (name_type_str, dests_str) = s.split_once(" -> ").unwrap();
-        let typ = match name_type_str.chars().next().unwrap() {
-            '%' => ModuleType::FlipFlop { state: false },
-            '&' => ModuleType::Conjunction {
-                inputs: HashMap::default(),
-            },
-            _ => ModuleType::Broadcaster,
-        };
-        let name = if let ModuleType::Broadcaster = &typ {
-            name_type_str.to_string()
-        } else {
-            name_type_str[1..].to_string()
-        };
-        let dests = dests_str.split(", ").map(str::to_string).collect();
-        Ok(Module { name, typ, dests })
+/**
+The first press on which every input of `conj` sends it a high pulse, found by timing each input's
+first few high pulses
+*/
+fn periodic_presses(circuit: &Circuit, conj: usize, max_presses: u64) -> Option<u64> {
+    const SAMPLES: usize = 3;
+
+    let inputs = &circuit.inputs[conj];
+    let mut highs = vec![Vec::new(); inputs.len()];
+    let mut state = circuit.initial_state();
+    for presses in 1..=max_presses {
+        circuit.press(&mut state, |pulse| {
+            if pulse.to == conj && pulse.high && highs[pulse.slot].last() != Some(&presses) {
+                highs[pulse.slot].push(presses);
+            }
+        });
+        if highs.iter().all(|h| h.len() >= SAMPLES) {
+            break;
+        }
     }
+
+    let mut congruences = Vec::new();
+    for (&input, h) in inputs.iter().zip(&highs) {
+        let name = circuit.names.name(input);
+        if h.len() < SAMPLES {
+            log::warn!("{name} is high {} times in {max_presses} presses", h.len());
+            return None;
+        }
+        let period = h[1] - h[0];
+        if h.windows(2).any(|w| w[1] - w[0] != period) {
+            log::warn!("{name} is not periodic: {h:?}");
+            return None;
+        }
+        log::info!("{name} is high every {period} presses from {}", h[0]);
+        congruences.push((i64::try_from(h[0]).unwrap(), i64::try_from(period).unwrap()));
+    }
+    let (r, m) = crt(&congruences)?;
+    let (r, m) = (u64::try_from(r).unwrap(), u64::try_from(m).unwrap());
+    let min = highs.iter().map(|h| h[0]).max().unwrap();
+    Some(if r >= min {
+        r
+    } else {
+        r + (min - r).div_ceil(m) * m
+    })
 }
 
-impl ModuleType {
-    fn is_flip_flip(&self) -> bool {
-        matches!(self, ModuleType::FlipFlop { .. })
+#[cfg(test)]
+mod test {
+    use super::{fewest_presses, Circuit, Trace};
+    use crate::Solution as _;
+
+    const EX1: &str = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+
+    const EX2: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+    /**
+    Binary counters built from flip-flops that each send a high pulse towards `rx` every
+    `period` presses, in the same shape as the real inputs
+    */
+    fn counters(periods: &[u64]) -> String {
+        let mut lines = Vec::new();
+        let mut starts = Vec::new();
+        for (c, period) in periods.iter().enumerate() {
+            let bits = 64 - period.leading_zeros();
+            let hub = format!("h{c}");
+            let mut hub_dests = vec![format!("b{c}_0"), format!("v{c}")];
+            for bit in 0..bits {
+                let mut dests = Vec::new();
+                if bit + 1 < bits {
+                    dests.push(format!("b{c}_{}", bit + 1));
+                }
+                if period & (1 << bit) != 0 {
+                    dests.push(hub.clone());
+                } else {
+                    hub_dests.push(format!("b{c}_{bit}"));
+                }
+                lines.push(format!("%b{c}_{bit} -> {}", dests.join(", ")));
+            }
+            lines.push(format!("&{hub} -> {}", hub_dests.join(", ")));
+            lines.push(format!("&v{c} -> f"));
+            starts.push(format!("b{c}_0"));
+        }
+        lines.push(format!("broadcaster -> {}", starts.join(", ")));
+        lines.push("&f -> rx".to_string());
+        lines.join("\n")
     }
 
-    fn is_conjuction(&self) -> bool {
-        matches!(self, ModuleType::Conjunction { .. })
+    #[test]
+    fn ex() {
+        assert_eq!("32000000", super::Solution.solve_1(EX1));
+        assert_eq!("11687500", super::Solution.solve_1(EX2));
+    }
+
+    #[test]
+    fn periodic() {
+        let input = counters(&[5, 7, 11]);
+        assert_eq!("385", super::Solution.solve_2(&input));
+
+        // a chain of flip-flops has no conjunction before rx, so it is simulated directly
+        let input = "broadcaster -> a\n%a -> b\n%b -> c\n%c -> rx";
+        assert_eq!("8", super::Solution.solve_2(input));
+
+        // an inverter only ever sends rx high pulses, so the search gives up
+        let circuit = Circuit::parse("broadcaster -> inv\n&inv -> rx");
+        let rx = circuit.names.id("rx").unwrap();
+        assert_eq!(None, fewest_presses(&circuit, rx, 1000));
+    }
+
+    #[test]
+    fn trace() {
+        let circuit = Circuit::parse(EX1);
+        let trace = Trace::record(&circuit, 2);
+        let first_press: Vec<_> = trace
+            .pulses
+            .iter()
+            .take_while(|p| p.press == 1)
+            .map(|p| {
+                let level = if p.high { "high" } else { "low" };
+                let (from, to) = (circuit.names.name(p.from), circuit.names.name(p.to));
+                format!("{from} -{level}-> {to}")
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "button -low-> broadcaster",
+                "broadcaster -low-> a",
+                "broadcaster -low-> b",
+                "broadcaster -low-> c",
+                "a -high-> b",
+                "b -high-> c",
+                "c -high-> inv",
+                "inv -low-> a",
+                "a -low-> b",
+                "b -low-> c",
+                "c -low-> inv",
+                "inv -high-> a",
+            ],
+            first_press
+        );
+        assert_eq!(24, trace.pulses.len());
+        assert_eq!((2, 11), (trace.pulses[12].press, trace.pulses[23].seq));
+
+        let inv = circuit.names.id("inv").unwrap();
+        let around_inv = trace.involving(&[inv]);
+        assert_eq!(8, around_inv.pulses.len());
+        assert!(around_inv
+            .pulses
+            .iter()
+            .all(|p| [p.from, p.to].contains(&inv)));
+
+        let mut vcd = Vec::new();
+        trace.write_vcd(&circuit, &mut vcd).unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        assert!(vcd.contains("$var wire 1 & inv $end"));
+        let around_b = super::Solution.vcd(EX1, &["b"]).unwrap();
+        assert!(around_b.contains(" b $end") && !around_b.contains(" inv $end"));
+        // a turns on with the fifth pulse of the first press, and off again with the ninth
+        assert!(vcd.contains("#4\n1#\n#5\n"));
+        assert!(vcd.contains("#8\n0#\n#9\n"));
     }
 }
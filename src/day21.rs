@@ -1,53 +1,79 @@
-use crate::util::{Grid, Point};
+use crate::util::{degree, newton_eval, Grid, Point};
+use num_bigint::BigInt;
+use std::collections::HashSet;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let map = parse_input(&input);
+    fn solve_1(&self, input: &str) -> String {
+        let (map, start) = parse_input(input);
 
-        let open_counts = open_counts(&map);
-        open_counts.center.to_string()
+        let counts = plots_by_distance(&map, start, 64);
+        reachable(&counts, 64).to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let map = parse_input(&input);
+    fn solve_2(&self, input: &str) -> String {
+        let (map, start) = parse_input(input);
+        assert_eq!(map.width(), map.height());
 
-        let open_counts = open_counts(&map);
+        // Every `size` steps the frontier reaches one more ring of copies of the map, so sampled at
+        // that period the number of reachable plots grows polynomially (quadratically, for the
+        // real inputs)
         let steps = 26_501_365;
-        let si = u64::try_from(map.width()).unwrap();
-        let chunks = (steps - (si - 1) / 2) / si; // how many axial chunks
-        log::info!("chunks {chunks}");
+        let size = usize::try_from(map.width()).unwrap();
+        let rem = steps % size;
+        let counts = plots_by_distance(&map, start, rem + 3 * size);
+        let samples: Vec<i64> = (0..4)
+            .map(|k| i64::try_from(reachable(&counts, rem + k * size)).unwrap())
+            .collect();
+        let degree = degree(&samples).expect("reachable plots don't grow polynomially");
+        log::info!("samples {samples:?} fit degree {degree}");
 
-        let full_even_count = chunks * chunks;
-        let full_odd_count = (chunks - 1) * (chunks - 1);
-        let total = full_even_count * open_counts.full_even
-            + full_odd_count * open_counts.full_odd
-            + open_counts.n
-            + open_counts.e
-            + open_counts.s
-            + open_counts.w
-            + chunks * open_counts.ne_small
-            + (chunks - 1) * open_counts.ne_big
-            + chunks * open_counts.se_small
-            + (chunks - 1) * open_counts.se_big
-            + chunks * open_counts.sw_small
-            + (chunks - 1) * open_counts.sw_big
-            + chunks * open_counts.nw_small
-            + (chunks - 1) * open_counts.nw_big;
-
-        total.to_string()
+        newton_eval(&samples, &BigInt::from(steps / size)).to_string()
     }
 }
 
-fn parse_input(input: &str) -> Grid<Tile> {
+fn parse_input(input: &str) -> (Grid<Tile>, Point) {
     let mut map: Grid<Tile> = input.parse().unwrap();
     let start = map
         .points_with_item()
         .find_map(|(p, t)| if let Tile::Start = t { Some(p) } else { None })
         .unwrap();
     map[start] = Tile::Open;
-    map
+    (map, start)
+}
+
+/**
+Number of plots at each distance from `start`, up to `max_steps`, with the map repeating infinitely
+in every direction
+*/
+fn plots_by_distance(map: &Grid<Tile>, start: Point, max_steps: usize) -> Vec<u64> {
+    let mut counts = vec![1];
+    let mut visited = HashSet::new();
+    visited.insert(start);
+    let mut frontier = vec![start];
+    for _ in 0..max_steps {
+        let mut next = Vec::new();
+        for p in frontier {
+            for a in p.adj() {
+                let wrapped = Point(a.0.rem_euclid(map.width()), a.1.rem_euclid(map.height()));
+                if matches!(map[wrapped], Tile::Open) && visited.insert(a) {
+                    next.push(a);
+                }
+            }
+        }
+        counts.push(next.len() as u64);
+        frontier = next;
+    }
+    counts
+}
+
+/**
+Plots that can be ended on after exactly `steps` steps. Stepping back and forth means that's every
+plot at a distance up to `steps` with the same parity.
+*/
+fn reachable(counts: &[u64], steps: usize) -> u64 {
+    counts[..=steps].iter().skip(steps % 2).step_by(2).sum()
 }
 
 enum Tile {
@@ -67,62 +93,29 @@ impl From<char> for Tile {
     }
 }
 
-#[derive(Default, Debug)]
-struct OpenCounts {
-    center: u64,
-    full_even: u64,
-    full_odd: u64,
-    n: u64,
-    e: u64,
-    s: u64,
-    w: u64,
-    ne_small: u64,
-    ne_big: u64,
-    se_small: u64,
-    se_big: u64,
-    sw_small: u64,
-    sw_big: u64,
-    nw_small: u64,
-    nw_big: u64,
-}
+#[cfg(test)]
+mod test {
+    use super::*;
 
-fn open_counts(map: &Grid<Tile>) -> OpenCounts {
-    assert_eq!(map.width(), map.height());
-    let size = map.height() - 1;
-    let hs = size / 2;
-    let mut counts = OpenCounts::default();
-    for (p, tile) in map.points_with_item() {
-        if let Tile::Rock = tile {
-            continue;
-        }
-        if p.adj()
-            .into_iter()
-            .all(|a| matches!(map.get(a), Some(Tile::Rock)))
-        {
-            continue;
-        }
-        let tests = [
-            (Point(hs, hs), 64, 0, &mut counts.center),
-            (Point(hs, hs), size + 1, 0, &mut counts.full_even),
-            (Point(hs, hs), size + 1, 1, &mut counts.full_odd),
-            (Point(hs, size), size, 1, &mut counts.n),
-            (Point(0, hs), size, 1, &mut counts.e),
-            (Point(hs, 0), size, 1, &mut counts.s),
-            (Point(size, hs), size, 1, &mut counts.w),
-            (Point(0, size), hs, 0, &mut counts.ne_small),
-            (Point(0, size), size + hs, 1, &mut counts.ne_big),
-            (Point(0, 0), hs, 0, &mut counts.se_small),
-            (Point(0, 0), size + hs, 1, &mut counts.se_big),
-            (Point(size, 0), hs, 0, &mut counts.sw_small),
-            (Point(size, 0), size + hs, 1, &mut counts.sw_big),
-            (Point(size, size), hs, 0, &mut counts.nw_small),
-            (Point(size, size), size + hs, 1, &mut counts.nw_big),
-        ];
-        for (start, dist, eo, dest) in tests {
-            if (p.0 + p.1) % 2 == eo && start.l1dist(p) <= dist {
-                *dest += 1;
-            }
-        }
+    const EX: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    #[test]
+    fn ex() {
+        let (map, start) = parse_input(EX);
+        let counts = plots_by_distance(&map, start, 100);
+        assert_eq!(16, reachable(&counts, 6));
+        assert_eq!(50, reachable(&counts, 10));
+        assert_eq!(1594, reachable(&counts, 50));
+        assert_eq!(6536, reachable(&counts, 100));
     }
-    counts
 }
@@ -1,192 +1,290 @@
-use crate::util::{parse_lines, Point};
+use crate::util::{parse_lines_with, Dot, Graph, NBox, Parse};
 use itertools::Itertools;
-use std::cmp::{max, min};
-use std::collections::{HashMap, HashSet};
-use std::fmt::Debug;
-use std::str::FromStr;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let graph = settle_bricks(&input);
-        graph
-            .supports
-            .iter()
-            .filter(|support| {
-                !support
-                    .iter()
-                    .copied()
-                    .any(|s| graph.supported_by[s].len() == 1)
-            })
+    fn solve_1(&self, input: &str) -> String {
+        let stack = BrickStack::settle(input);
+        let falls = stack.chain_reactions();
+        stack
+            .bricks()
+            .filter(|(i, _)| falls[*i] == 0)
             .count()
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let graph = settle_bricks(&input);
-        (0..graph.supports.len())
-            .map(|base| {
-                let n = supports(&graph, base);
-                log::debug!("{base} -> {n}");
-                n
-            })
-            .sum::<usize>()
-            .to_string()
+    fn solve_2(&self, input: &str) -> String {
+        let stack = BrickStack::settle(input);
+        log::trace!("settled:\n{stack}");
+        let falls = stack.chain_reactions();
+        for (base, n) in falls.iter().enumerate() {
+            log::debug!("{base} -> {n}");
+        }
+        falls.iter().sum::<usize>().to_string()
+    }
+
+    /**
+    Which bricks rest on which. Bricks that are the only support of another brick are red.
+    */
+    fn dot(&self, input: &str) -> Option<Dot> {
+        let graph = BrickStack::settle(input).graph();
+        let mut dot = Dot::digraph("supports");
+        for (i, supports) in graph.supports.iter().enumerate() {
+            let sole_support = supports.iter().any(|s| graph.supported_by[*s].len() == 1);
+            let color = if sole_support { "red" } else { "black" };
+            dot.node(&i.to_string(), &[("color", color)]);
+            for s in supports.iter().sorted() {
+                dot.edge(&i.to_string(), &s.to_string(), &[]);
+            }
+        }
+        Some(dot)
     }
 }
 
-fn settle_bricks(input: &str) -> BrickGraph {
-    let mut bricks = BrickStack {
-        bricks: parse_lines(input).collect(),
-    };
-    bricks.bricks.sort_by_key(Brick::bottom);
-    bricks.settle()
+#[derive(Parse)]
+#[parse("{x},{y},{z}~{x2},{y2},{z2}")]
+struct BrickEnds {
+    x: i64,
+    y: i64,
+    z: i64,
+    x2: i64,
+    y2: i64,
+    z2: i64,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-struct Point3(i32, i32, i32);
+fn parse_brick(line: &str) -> NBox<3> {
+    let e: BrickEnds = line.parse().unwrap();
+    NBox::from_corners([e.x, e.y, e.z], [e.x2, e.y2, e.z2])
+}
 
-#[derive(Debug)]
-struct Brick {
-    start: Point3,
-    end: Point3,
+/**
+Bricks resting on the ground, which is at z = 0, and on each other. Bricks are identified by the
+order they were added in, and a removed brick's id is not reused.
+*/
+#[derive(Clone, Default)]
+struct BrickStack {
+    bricks: Vec<Option<NBox<3>>>,
 }
 
-impl Brick {
-    fn bottom(&self) -> i32 {
-        min(self.start.2, self.end.2)
-    }
+impl BrickStack {
+    /**
+    Drops the bricks in the input in order of height, so lower ones get lower ids
+    */
+    fn settle(input: &str) -> BrickStack {
+        let mut bricks: Vec<_> = parse_lines_with(input, parse_brick).collect();
+        bricks.sort_by_key(|b| b.min[2]);
 
-    fn points(&self) -> Vec<Point3> {
-        if self.start.0 == self.end.0 && self.start.1 == self.end.1 {
-            let range = if self.start.2 < self.end.2 {
-                self.start.2..=self.end.2
-            } else {
-                self.end.2..=self.start.2
-            };
-            range
-                .map(|z| Point3(self.start.0, self.start.1, z))
-                .collect()
-        } else if self.start.0 == self.end.0 && self.start.2 == self.end.2 {
-            let range = if self.start.1 < self.end.1 {
-                self.start.1..=self.end.1
-            } else {
-                self.end.1..=self.start.1
-            };
-            range
-                .map(|y| Point3(self.start.0, y, self.start.2))
-                .collect()
-        } else {
-            let range = if self.start.0 < self.end.0 {
-                self.start.0..=self.end.0
-            } else {
-                self.end.0..=self.start.0
-            };
-            range
-                .map(|x| Point3(x, self.start.1, self.start.2))
-                .collect()
+        let mut stack = BrickStack::default();
+        for falling in bricks {
+            stack.add(falling);
         }
+        stack
     }
-}
 
-impl FromStr for Brick {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (start_str, end_str) = s.split_once('~').unwrap();
-        let start = {
-            let mut iter = start_str.split(',');
-            Point3(
-                iter.next().unwrap().parse().unwrap(),
-                iter.next().unwrap().parse().unwrap(),
-                iter.next().unwrap().parse().unwrap(),
-            )
-        };
-        let end = {
-            let mut iter = end_str.split(',');
-            Point3(
-                iter.next().unwrap().parse().unwrap(),
-                iter.next().unwrap().parse().unwrap(),
-                iter.next().unwrap().parse().unwrap(),
-            )
-        };
-        Ok(Brick { start, end })
+    fn bricks(&self) -> impl Iterator<Item = (usize, NBox<3>)> + '_ {
+        self.bricks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.map(|b| (i, b)))
     }
-}
 
-struct BrickStack {
-    bricks: Vec<Brick>,
-}
+    /**
+    Drops `brick` from where it is until it lands, returning its id. Nothing else moves.
+    */
+    fn add(&mut self, brick: NBox<3>) -> usize {
+        assert!(
+            self.bricks().all(|(_, b)| !b.overlaps(&brick)),
+            "{brick:?} is inside another brick"
+        );
+        let i = self.bricks.len();
+        log::debug!("dropping brick {i} = {brick:?}");
+        let dropped = self.dropped(&brick);
+        log::debug!("dropped: {dropped:?}");
+        self.bricks.push(Some(dropped));
+        i
+    }
 
-impl BrickStack {
-    fn settle(&mut self) -> BrickGraph {
-        let mut graph = BrickGraph::default();
-        let mut floors: HashMap<Point, (i32, usize)> = HashMap::new();
-        for i in 0..self.bricks.len() {
-            let falling = &self.bricks[i];
-            log::debug!("dropping brick {i} = {falling:?}");
-            let points: HashSet<_> = falling
-                .points()
-                .into_iter()
-                .map(|p| Point(p.0, p.1))
-                .collect();
-
-            let floor_bricks = points
-                .iter()
-                .filter_map(|fall_p| floors.get(fall_p))
-                .copied()
-                .max_set_by_key(|(h, _)| *h);
-            let floor = floor_bricks.first().map(|(h, _)| *h).unwrap_or_default();
-            let d = falling.bottom() - floor - 1;
-
-            let falling = &mut self.bricks[i];
-            falling.start.2 -= d;
-            falling.end.2 -= d;
-
-            let top = max(falling.start.2, falling.end.2);
-            for p in points {
-                floors.insert(p, (top, i));
+    /**
+    Takes a brick out and lets everything that was held up by it fall, returning the ids of the
+    bricks that moved
+    */
+    fn remove(&mut self, id: usize) -> Vec<usize> {
+        let removed = self.bricks[id].take().expect("no such brick");
+        // every brick that moves only uncovers bricks higher up than it, so taking them lowest
+        // first lets each one land on bricks that have already finished falling
+        let mut falling: BTreeSet<_> = self.resting_on(&removed).collect();
+        let mut moved = Vec::new();
+        while let Some((_, i)) = falling.pop_first() {
+            let brick = self.bricks[i].take().unwrap();
+            let dropped = self.dropped(&brick);
+            self.bricks[i] = Some(dropped);
+            if dropped != brick {
+                moved.push(i);
+                falling.extend(self.resting_on(&brick));
             }
-            let floor_bricks: HashSet<_> = floor_bricks.into_iter().map(|(_, fb)| fb).collect();
-            for fb in &floor_bricks {
-                graph.supports[*fb].insert(i);
+        }
+        moved
+    }
+
+    /**
+    Where `brick` lands if dropped from where it is
+    */
+    fn dropped(&self, brick: &NBox<3>) -> NBox<3> {
+        let footprint = brick.project::<2>();
+        let floor = self
+            .bricks()
+            .filter(|(_, b)| b.max[2] <= brick.min[2] && b.project::<2>().overlaps(&footprint))
+            .map(|(_, b)| b.max[2])
+            .max()
+            .unwrap_or(1);
+        brick.translate([0, 0, floor - brick.min[2]])
+    }
+
+    /**
+    The bricks directly on top of `brick`, with the height of their bottoms
+    */
+    fn resting_on<'a>(&'a self, brick: &'a NBox<3>) -> impl Iterator<Item = (i64, usize)> + 'a {
+        let footprint = brick.project::<2>();
+        self.bricks()
+            .filter(move |(_, b)| b.min[2] == brick.max[2] && b.project::<2>().overlaps(&footprint))
+            .map(|(i, b)| (b.min[2], i))
+    }
+
+    /**
+    Which bricks rest directly on which, only comparing bricks whose tops and bottoms meet
+    */
+    fn graph(&self) -> BrickGraph {
+        let mut graph = BrickGraph {
+            supports: vec![HashSet::new(); self.bricks.len()],
+            supported_by: vec![HashSet::new(); self.bricks.len()],
+        };
+        let mut by_bottom: HashMap<i64, Vec<(usize, NBox<2>)>> = HashMap::new();
+        for (i, b) in self.bricks() {
+            by_bottom
+                .entry(b.min[2])
+                .or_default()
+                .push((i, b.project::<2>()));
+        }
+        for (i, brick) in self.bricks() {
+            let footprint = brick.project::<2>();
+            let above = by_bottom.get(&brick.max[2]).map_or(&[][..], Vec::as_slice);
+            for (j, _) in above.iter().filter(|(_, f)| f.overlaps(&footprint)) {
+                graph.supports[i].insert(*j);
+                graph.supported_by[*j].insert(i);
             }
-            graph.supports.push(HashSet::new());
-            graph.supported_by.push(floor_bricks);
-            log::debug!("dropped: {falling:?}");
         }
         graph
     }
+
+    /**
+    How many other bricks fall if each brick is removed. A brick falls exactly when every way down
+    from it to the ground passes through the removed brick, which is when the removed brick
+    dominates it in the graph of supports rooted at the ground.
+    */
+    fn chain_reactions(&self) -> Vec<usize> {
+        let graph = self.graph();
+        let mut supports: Graph<Option<usize>> = Graph::new();
+        let ground = supports.add_node(None);
+        let node: Vec<_> = (0..self.bricks.len())
+            .map(|i| supports.add_node(Some(i)))
+            .collect();
+        for (i, _) in self.bricks() {
+            if graph.supported_by[i].is_empty() {
+                supports.add_edge(ground, node[i], ());
+            }
+            for &s in &graph.supported_by[i] {
+                supports.add_edge(node[s], node[i], ());
+            }
+        }
+
+        let idom = supports.immediate_dominators(ground);
+        let mut falls = vec![0; self.bricks.len()];
+        for (i, _) in self.bricks() {
+            let mut dominator = idom[node[i]].unwrap();
+            while let Some(d) = *supports.node(dominator) {
+                falls[d] += 1;
+                dominator = idom[dominator].unwrap();
+            }
+        }
+        falls
+    }
+}
+
+/**
+The settled bricks, one per line in the same format as the input
+*/
+impl Display for BrickStack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (_, b) in self.bricks() {
+            writeln!(
+                f,
+                "{},{},{}~{},{},{}",
+                b.min[0],
+                b.min[1],
+                b.min[2],
+                b.max[0] - 1,
+                b.max[1] - 1,
+                b.max[2] - 1
+            )?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Default)]
 struct BrickGraph {
     supports: Vec<HashSet<usize>>,
     supported_by: Vec<HashSet<usize>>,
 }
 
-fn supports(graph: &BrickGraph, start: usize) -> usize {
-    let mut candidates = graph.supports[start].clone();
-    let mut set = HashSet::new();
-    set.insert(start);
-    loop {
-        let next = candidates
-            .iter()
-            .copied()
-            .filter(|node| {
-                let supported_by = &graph.supported_by[*node];
-                !supported_by.is_empty() && supported_by.iter().all(|s| set.contains(s))
-            })
-            .collect_vec();
-        if next.is_empty() {
-            break;
-        }
-        for n in &next {
-            candidates.remove(n);
-            candidates.extend(&graph.supports[*n]);
+#[cfg(test)]
+mod test {
+    use super::{parse_brick, BrickStack};
+    use crate::Solution as _;
+
+    const EX: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+    #[test]
+    fn ex() {
+        assert_eq!("5", super::Solution.solve_1(EX));
+        assert_eq!("7", super::Solution.solve_2(EX));
+    }
+
+    #[test]
+    fn simulate() {
+        let stack = BrickStack::settle(EX);
+        let settled = stack.to_string();
+        assert_eq!(Some("1,1,5~1,1,6"), settled.lines().last());
+        assert_eq!(settled, BrickStack::settle(&settled).to_string());
+
+        let falls = stack.chain_reactions();
+        assert_eq!(vec![6, 0, 0, 0, 0, 1, 0], falls);
+        for (i, n) in falls.into_iter().enumerate() {
+            assert_eq!(n, stack.clone().remove(i).len(), "{i}");
         }
-        set.extend(next);
+
+        let mut stack = stack;
+        let top = stack.add(parse_brick("0,1,20~2,1,20"));
+        assert_eq!(2, stack.chain_reactions()[5]);
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, top], stack.remove(0));
+        assert_eq!(
+            "0,0,1~2,0,1
+0,2,1~2,2,1
+0,0,2~0,2,2
+2,0,2~2,2,2
+0,1,3~2,1,3
+1,1,4~1,1,5
+0,1,6~2,1,6
+",
+            stack.to_string()
+        );
     }
-    set.len() - 1
 }
@@ -4,7 +4,7 @@ use itertools::Itertools;
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
+    fn solve_1(&self, input: &str) -> String {
         let maze = Maze::new(input.parse().unwrap(), false);
         log::debug!("nodes: {:#?}", maze.nodes);
         log::debug!("matrix: {:#?}", maze.matrix);
@@ -12,7 +12,7 @@ impl crate::Solution for Solution {
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
+    fn solve_2(&self, input: &str) -> String {
         let maze = Maze::new(input.parse().unwrap(), true);
         log::info!("{} nodes", maze.nodes.len());
         log::debug!("matrix: {:#?}", maze.matrix);
@@ -1,13 +1,14 @@
+use crate::util::{ints, parse_lines_with, rational, solve_linear};
 use itertools::Itertools;
-use crate::util::parse_lines_with;
+use num_rational::BigRational;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
+    fn solve_1(&self, input: &str) -> String {
         let min_bound = 200000000000000.0;
         let max_bound = 400000000000000.0;
-        let lines = parse_lines_with(&input, parse_line).collect_vec();
+        let lines = parse_lines_with(input, parse_line).collect_vec();
         let mut res = 0;
         for i1 in 0..lines.len() {
             for i2 in (i1 + 1)..lines.len() {
@@ -24,46 +25,78 @@ impl crate::Solution for Solution {
         res.to_string()
     }
 
-    fn solve_2(&self, _input: String) -> String {
-        /*
-        385803404726014, 386664184220541, 365612177547870 @ -192, -149, -36
-         67771006464582, 193910554798739,  21517103663672 @  280,  136, 426
-        334054450538558, 356919582763697, 188448277532212 @   84,  -25, -48
-        312676332944619, 337964672568504,  98227917525187 @  -35,  -48, 372
+    fn solve_2(&self, input: &str) -> String {
+        let hail = parse_lines_with(input, parse_line).collect_vec();
+        // any three hailstones whose equations are independent pin down the rock
+        let rock = (0..hail.len())
+            .tuple_combinations()
+            .find_map(|(i, j, k)| rock_position(hail[i], hail[j], hail[k]))
+            .expect("no rock trajectory");
+        log::info!("rock at {rock:?}");
+        (rock.x + rock.y + rock.z).to_string()
+    }
+}
 
-        0 = (-149 - y_2)*(385803404726014 - x_1) + (x_2 - -192)*(386664184220541 - y_1)
-        0 = (136 - y_2)*(67771006464582 - x_1) + (x_2 - 280)*(193910554798739 - y_1)
-        0 = (-25 - y_2)*(334054450538558 - x_1) + (x_2 - 84)*(356919582763697 - y_1)
-        0 = (-48 - y_2)*(312676332944619 - x_1) + (x_2 - -35)*(337964672568504 - y_1)
+/**
+Finds the position P of a rock thrown with velocity V that hits all three hailstones. For each
+hailstone, (P - p) x (V - v) = 0, since the rock and the stone are at the same place at some time.
+The P x V term is the same for every hailstone, so subtracting the equations of two stones gives
+three equations linear in P and V, and two pairs give a 6x6 system.
+*/
+fn rock_position(
+    h1: (Point3, Point3),
+    h2: (Point3, Point3),
+    h3: (Point3, Point3),
+) -> Option<Point3> {
+    let mut matrix = Vec::new();
+    let mut rhs = Vec::new();
+    for ((p1, v1), (p2, v2)) in [(h1, h2), (h1, h3)] {
+        let dv = sub(wide(v2), wide(v1));
+        let dp = sub(wide(p2), wide(p1));
+        // P x dv + dp x V = p2 x v2 - p1 x v1
+        matrix.push([0, dv[2], -dv[1], 0, -dp[2], dp[1]]);
+        matrix.push([-dv[2], 0, dv[0], dp[2], 0, -dp[0]]);
+        matrix.push([dv[1], -dv[0], 0, -dp[1], dp[0], 0]);
+        rhs.extend(sub(cross(wide(p2), wide(v2)), cross(wide(p1), wide(v1))));
+    }
+    let matrix = matrix
+        .into_iter()
+        .map(|row| row.into_iter().map(rational).collect())
+        .collect_vec();
+    let rhs = rhs.into_iter().map(rational).collect_vec();
+
+    let solution = solve_linear(&matrix, &rhs)?;
+    let coord = |r: &BigRational| {
+        assert!(r.is_integer(), "rock position {r} is not an integer");
+        i64::try_from(r.to_integer()).unwrap()
+    };
+    Some(Point3::new(
+        coord(&solution[0]),
+        coord(&solution[1]),
+        coord(&solution[2]),
+    ))
+}
 
-        x_1 = 363206674204110 ∧ x_2 = -164 ∧ y_1 = 368909610239045 ∧ y_2 = -127
+fn wide(p: Point3) -> [i128; 3] {
+    [p.x.into(), p.y.into(), p.z.into()]
+}
 
-        0 = (-36 - z_2)*(386664184220541 - y_1) + (y_2 - -149)*(365612177547870 - z_1)
-        0 = (426 - z_2)*(193910554798739 - y_1) + (y_2 - 136)*(21517103663672 - z_1)
-        0 = (-48 - z_2)*(356919582763697 - y_1) + (y_2 - -25)*(188448277532212 - z_1)
-        0 = (372 - z_2)*(337964672568504 - y_1) + (y_2 - -48)*(98227917525187 - z_1)
+fn sub(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
 
-        y_1 = 368909610239045 ∧ y_2 = -127 ∧ z_1 = 156592420220258 ∧ z_2 = 223
-         */
-        (363206674204110_i64 + 368909610239045 + 156592420220258).to_string()
-    }
+fn cross(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
 }
 
 fn parse_line(s: &str) -> (Point3, Point3) {
-    let (p_str, v_str) = s.split_once(" @ ").unwrap();
-    let mut p_parts = p_str.split(", ").map(|it| it.trim());
-    let p = Point3 {
-        x: p_parts.next().unwrap().parse().unwrap(),
-        y: p_parts.next().unwrap().parse().unwrap(),
-        z: p_parts.next().unwrap().parse().unwrap(),
-    };
-    let mut v_parts = v_str.split(", ").map(|it| it.trim());
-    let v = Point3 {
-        x: v_parts.next().unwrap().parse().unwrap(),
-        y: v_parts.next().unwrap().parse().unwrap(),
-        z: v_parts.next().unwrap().parse().unwrap(),
-    };
-    (p, v)
+    let n: Vec<i64> = ints(s).unwrap();
+    assert_eq!(6, n.len(), "expected position and velocity in {s:?}");
+    (Point3::new(n[0], n[1], n[2]), Point3::new(n[3], n[4], n[5]))
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -127,9 +160,11 @@ fn intersect_3d(p1: Point3, v1: Point3, p2: Point3, v2: Point3) -> Option<Point3
     Some(Point3::new(ix, iy, z1))
 }
 
+#[cfg(test)]
 mod test {
-    use itertools::assert_equal;
     use super::*;
+    use crate::Solution as _;
+    use itertools::assert_equal;
 
     #[test]
     fn test_intersect_3d() {
@@ -139,4 +174,23 @@ mod test {
         );
         assert_equal(Some(Point3::new(24, 13, 10)), i);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn ex2() {
+        let input = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+        assert_eq!("47", Solution.solve_2(input));
+    }
+
+    #[test]
+    fn solved_by_hand() {
+        let input = "385803404726014, 386664184220541, 365612177547870 @ -192, -149, -36
+67771006464582, 193910554798739, 21517103663672 @ 280, 136, 426
+334054450538558, 356919582763697, 188448277532212 @ 84, -25, -48
+312676332944619, 337964672568504, 98227917525187 @ -35, -48, 372";
+        assert_eq!("888708704663413", Solution.solve_2(input));
+    }
+}
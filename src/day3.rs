@@ -4,8 +4,8 @@ use std::collections::{HashMap, HashSet};
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let (numbers, symbols) = find_things(&input);
+    fn solve_1(&self, input: &str) -> String {
+        let (numbers, symbols) = find_things(input);
         let symbols: HashSet<_> = symbols.into_keys().collect();
 
         numbers
@@ -16,8 +16,8 @@ impl crate::Solution for Solution {
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let (numbers, symbols) = find_things(&input);
+    fn solve_2(&self, input: &str) -> String {
+        let (numbers, symbols) = find_things(input);
         symbols
             .into_iter()
             .filter(|(_, c)| *c == '*')
@@ -131,7 +131,7 @@ mod test {
 ...$.*....
 .664.598.."
             .to_string();
-        let res = Solution.solve_1(inp);
+        let res = Solution.solve_1(&inp);
         assert_eq!(res, "4361");
     }
 }
@@ -1,19 +1,18 @@
-use crate::util::parse_lines;
+use crate::util::{ints, parse_lines, Parse, ParseError};
 use std::collections::HashSet;
-use std::str::FromStr;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        parse_lines(&input)
+    fn solve_1(&self, input: &str) -> String {
+        parse_lines(input)
             .map(|card: Card| card.points())
             .sum::<i32>()
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let cards: Vec<Card> = parse_lines(&input).collect();
+    fn solve_2(&self, input: &str) -> String {
+        let cards: Vec<Card> = parse_lines(input).collect();
         let mut counts = vec![1; cards.len()];
         for card in cards {
             let id = card.id;
@@ -27,9 +26,13 @@ impl crate::Solution for Solution {
     }
 }
 
+#[derive(Parse)]
+#[parse("Card {id}: {winning} | {picked}")]
 struct Card {
     id: usize,
+    #[parse(with = int_set)]
     winning: HashSet<i32>,
+    #[parse(with = ints)]
     picked: Vec<i32>,
 }
 
@@ -51,35 +54,8 @@ impl Card {
     }
 }
 
-impl FromStr for Card {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (head, tail) = s.split_once(':').ok_or_else(|| s.to_string())?;
-
-        let id = head
-            .chars()
-            .skip(5)
-            .collect::<String>()
-            .trim()
-            .parse()
-            .map_err(|_| s.to_string())?;
-
-        let (winning_str, picked_str) = tail.split_once('|').ok_or_else(|| s.to_string())?;
-        let winning = winning_str
-            .split_ascii_whitespace()
-            .map(|it| it.parse().unwrap())
-            .collect();
-        let picked = picked_str
-            .split_ascii_whitespace()
-            .map(|it| it.parse().unwrap())
-            .collect();
-        Ok(Card {
-            id,
-            winning,
-            picked,
-        })
-    }
+fn int_set(s: &str) -> Result<HashSet<i32>, ParseError> {
+    Ok(ints(s)?.into_iter().collect())
 }
 
 #[cfg(test)]
@@ -97,7 +73,7 @@ Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
             .to_string();
 
-        let res = Solution.solve_1(inp);
+        let res = Solution.solve_1(&inp);
         assert_eq!("13", res);
     }
 }
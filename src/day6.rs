@@ -1,8 +1,8 @@
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        parse_input(&input)
+    fn solve_1(&self, input: &str) -> String {
+        parse_input(input)
             .into_iter()
             .map(|race| {
                 let h = hold_time(race);
@@ -12,8 +12,8 @@ impl crate::Solution for Solution {
             .to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let race = parse_input_2(&input);
+    fn solve_2(&self, input: &str) -> String {
+        let race = parse_input_2(input);
         let h = hold_time_2(race);
         let ways = race.0 - 2 * h + 1;
         ways.to_string()
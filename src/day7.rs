@@ -1,131 +1,59 @@
-use crate::util::parse_lines;
-use std::cmp::Ordering;
-use std::str::FromStr;
+use crate::util::{parse_lines, Parse, ParseError};
+use itertools::Itertools;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let mut hands: Vec<HandBid> = parse_lines(&input).collect();
-        hands.sort_by(|a, b| a.hand.cmp(&b.hand));
-        sum_winnings(hands)
+    fn solve_1(&self, input: &str) -> String {
+        let hands: Vec<HandBid> = parse_lines(input).collect();
+        for (name, rules) in &RULESETS {
+            log::info!("{name}: {}", winnings(&hands, rules));
+        }
+        winnings(&hands, &STANDARD).to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let mut hands: Vec<HandBid> = parse_lines(&input).collect();
-        for hand in &mut hands {
-            hand.hand.translate_to_part_2();
-        }
-        hands.sort_by(|a, b| a.hand.cmp(&b.hand));
-        sum_winnings(hands)
+    fn solve_2(&self, input: &str) -> String {
+        let hands: Vec<HandBid> = parse_lines(input).collect();
+        winnings(&hands, &JOKERS).to_string()
     }
 }
 
-fn sum_winnings(hands: Vec<HandBid>) -> String {
+fn winnings(hands: &[HandBid], rules: &Ruleset) -> u32 {
     hands
-        .into_iter()
+        .iter()
+        .map(|hb| (rules.key(&hb.hand), hb.bid))
+        .sorted()
         .enumerate()
-        .map(|(i, hb)| {
+        .map(|(i, (_, bid))| {
             let rank: u32 = (i + 1).try_into().unwrap();
-            rank * hb.bid
+            rank * bid
         })
-        .sum::<u32>()
-        .to_string()
+        .sum()
 }
 
+#[derive(Parse)]
+#[parse("{hand} {bid}")]
 struct HandBid {
+    #[parse(with = parse_hand)]
     hand: Hand,
     bid: u32,
 }
 
-impl FromStr for HandBid {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (hand_str, bid_str) = s.split_once(' ').unwrap();
-        let cards = hand_str
-            .chars()
-            .map(card_value)
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
-        let hand = Hand {
-            cards,
-            joker_rule: false,
-        };
-        let bid = bid_str.parse().unwrap();
-        Ok(HandBid { hand, bid })
+fn parse_hand(s: &str) -> Result<Hand, ParseError> {
+    if let Some(i) = s.find(|c| !STANDARD.order.contains(c)) {
+        let card = s[i..].chars().next().unwrap();
+        return Err(ParseError::at(i, format!("{card:?} is not a card")));
     }
+    let cards: Vec<_> = s.chars().collect();
+    let cards = cards.try_into().map_err(|cards: Vec<_>| {
+        ParseError::at(0, format!("expected 5 cards, got {}", cards.len()))
+    })?;
+    Ok(Hand { cards })
 }
 
-fn card_value(c: char) -> u8 {
-    match c {
-        '2' => 0,
-        '3' => 1,
-        '4' => 2,
-        '5' => 3,
-        '6' => 4,
-        '7' => 5,
-        '8' => 6,
-        '9' => 7,
-        'T' => 8,
-        'J' => 9,
-        'Q' => 10,
-        'K' => 11,
-        'A' => 12,
-        _ => panic!("card {c}"),
-    }
-}
-
-#[derive(Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 struct Hand {
-    cards: [u8; 5],
-    joker_rule: bool,
-}
-
-impl Hand {
-    fn translate_to_part_2(&mut self) {
-        self.joker_rule = true;
-        for i in 0..5 {
-            match self.cards[i].cmp(&9) {
-                Ordering::Less => self.cards[i] += 1,
-                Ordering::Equal => self.cards[i] = 0,
-                Ordering::Greater => {}
-            }
-        }
-    }
-
-    fn get_type(&self) -> Type {
-        let mut groups = [0u8; 13];
-        for card in self.cards {
-            groups[card as usize] += 1;
-        }
-
-        if self.joker_rule {
-            let j = std::mem::take(&mut groups[0]);
-            let highest = groups.iter_mut().max().unwrap();
-            *highest += j;
-        }
-
-        let highest = std::mem::take(groups.iter_mut().max().unwrap());
-        let second = groups.into_iter().max().unwrap();
-
-        if highest == 5 {
-            Type::FiveOfKind
-        } else if highest == 4 {
-            Type::FourOfKind
-        } else if highest == 3 && second == 2 {
-            Type::FullHouse
-        } else if highest == 3 {
-            Type::ThreeOfKind
-        } else if highest == 2 && second == 2 {
-            Type::TwoPair
-        } else if highest == 2 {
-            Type::OnePair
-        } else {
-            Type::HighCard
-        }
-    }
+    cards: [char; 5],
 }
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
@@ -134,36 +62,170 @@ enum Type {
     OnePair,
     TwoPair,
     ThreeOfKind,
+    Straight,
     FullHouse,
     FourOfKind,
     FiveOfKind,
 }
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+/**
+How hands tie on type are ordered
+*/
+#[derive(Copy, Clone, Debug)]
+enum TieBreak {
+    /**
+    By the first card that differs, in the order they were dealt
+    */
+    DealtOrder,
+    /**
+    By the cards of the biggest group first, like poker kickers
+    */
+    BiggestGroups,
+}
+
+/**
+House rules for ranking hands
+*/
+#[derive(Clone, Debug)]
+struct Ruleset {
+    /**
+    Every card, weakest first
+    */
+    order: &'static str,
+    /**
+    A card that counts as whichever card makes the strongest type, but is still ranked by `order`
+    for tie-breaks
+    */
+    wildcard: Option<char>,
+    /**
+    Whether five cards of consecutive strength form a straight
+    */
+    straights: bool,
+    tie_break: TieBreak,
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if self == other {
-            return Ordering::Equal;
+const STANDARD: Ruleset = Ruleset {
+    order: "23456789TJQKA",
+    wildcard: None,
+    straights: false,
+    tie_break: TieBreak::DealtOrder,
+};
+
+const JOKERS: Ruleset = Ruleset {
+    order: "J23456789TQKA",
+    wildcard: Some('J'),
+    ..STANDARD
+};
+
+const RULESETS: [(&str, Ruleset); 4] = [
+    ("standard", STANDARD),
+    ("jokers", JOKERS),
+    (
+        "straights",
+        Ruleset {
+            straights: true,
+            ..STANDARD
+        },
+    ),
+    (
+        "poker",
+        Ruleset {
+            straights: true,
+            tie_break: TieBreak::BiggestGroups,
+            ..STANDARD
+        },
+    ),
+];
+
+impl Ruleset {
+    fn strength(&self, card: char) -> u8 {
+        let i = self
+            .order
+            .find(card)
+            .unwrap_or_else(|| panic!("card {card}"));
+        i.try_into().unwrap()
+    }
+
+    fn hand_type(&self, hand: &Hand) -> Type {
+        let wild = hand
+            .cards
+            .iter()
+            .filter(|c| Some(**c) == self.wildcard)
+            .count();
+        let strengths: Vec<_> = hand
+            .cards
+            .iter()
+            .filter(|c| Some(**c) != self.wildcard)
+            .map(|c| self.strength(*c))
+            .collect();
+
+        // wildcards always do best joining the biggest group
+        let mut groups: Vec<_> = strengths.iter().counts().into_values().collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        if groups.is_empty() {
+            groups.push(0);
         }
+        groups[0] += wild;
+        let by_groups = match groups[..] {
+            [5] => Type::FiveOfKind,
+            [4, ..] => Type::FourOfKind,
+            [3, 2] => Type::FullHouse,
+            [3, ..] => Type::ThreeOfKind,
+            [2, 2, ..] => Type::TwoPair,
+            [2, ..] => Type::OnePair,
+            _ => Type::HighCard,
+        };
 
-        let type_cmp = self.get_type().cmp(&other.get_type());
-        if type_cmp == Ordering::Equal {
-            self.cards.cmp(&other.cards)
+        if self.straights && self.is_straight(&strengths) {
+            by_groups.max(Type::Straight)
         } else {
-            type_cmp
+            by_groups
         }
     }
+
+    /**
+    Whether the non-wild cards, with wildcards filling the gaps, can be five consecutive cards
+    */
+    fn is_straight(&self, strengths: &[u8]) -> bool {
+        let (Some(low), Some(high)) = (strengths.iter().min(), strengths.iter().max()) else {
+            return self.order.len() >= 5;
+        };
+        let (low, high) = (usize::from(*low), usize::from(*high));
+        // some run of five cards starting at or below the lowest card must reach the highest
+        strengths.iter().all_unique()
+            && high.saturating_sub(4) <= low.min(self.order.len().saturating_sub(5))
+    }
+
+    /**
+    A key that sorts hands from weakest to strongest
+    */
+    fn key(&self, hand: &Hand) -> (Type, Vec<u8>) {
+        let strengths = hand.cards.iter().map(|c| self.strength(*c));
+        let tie_break = match self.tie_break {
+            TieBreak::DealtOrder => strengths.collect(),
+            TieBreak::BiggestGroups => {
+                let counts = strengths.clone().counts();
+                strengths
+                    .sorted_by_key(|s| std::cmp::Reverse((counts[s], *s)))
+                    .collect()
+            }
+        };
+        (self.hand_type(hand), tie_break)
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use super::{parse_hand, Hand, Ruleset, Type, JOKERS, RULESETS, STANDARD};
     use crate::day7::Solution;
     use crate::init_test_logging;
+    use itertools::Itertools;
+
+    const EX: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
 
     #[test]
     fn ex2() {
@@ -177,8 +239,70 @@ KTJJT 220
 QQQJA 483"
             .to_string();
 
-        let res = Solution.solve_2(input);
+        let res = Solution.solve_2(&input);
 
         assert_eq!("5905", res);
     }
+
+    fn hand_type(rules: &Ruleset, hand: &str) -> Type {
+        rules.hand_type(&parse_hand(hand).unwrap())
+    }
+
+    #[test]
+    fn house_rules() {
+        use crate::Solution as _;
+        assert_eq!("6440", Solution.solve_1(EX));
+
+        let straights = &RULESETS[2].1;
+        assert_eq!(Type::Straight, hand_type(straights, "65874"));
+        assert_eq!(Type::HighCard, hand_type(&STANDARD, "65874"));
+        assert_eq!(Type::HighCard, hand_type(straights, "65873"));
+        assert_eq!(Type::Straight, hand_type(straights, "TJQKA"));
+
+        let wild_straights = Ruleset {
+            straights: true,
+            ..JOKERS
+        };
+        assert_eq!(Type::Straight, hand_type(&wild_straights, "QJKAJ"));
+        assert_eq!(Type::Straight, hand_type(&wild_straights, "2J4J6"));
+        assert_eq!(Type::FourOfKind, hand_type(&wild_straights, "2J2J6"));
+        assert_eq!(Type::Straight, hand_type(&wild_straights, "J2J34"));
+        assert_eq!(Type::FiveOfKind, hand_type(&wild_straights, "JJJJJ"));
+
+        // poker kickers: the pair of 3s beats the pair of 2s, whatever order they were dealt in
+        let poker = &RULESETS[3].1;
+        let a = poker.key(&parse_hand("A2K23").unwrap());
+        let b = poker.key(&parse_hand("3Q3KA").unwrap());
+        assert!(a < b);
+        assert!(
+            STANDARD.key(&parse_hand("A2K23").unwrap())
+                > STANDARD.key(&parse_hand("3Q3KA").unwrap())
+        );
+
+        let err = parse_hand("32é3K").unwrap_err();
+        assert_eq!("column 3: 'é' is not a card", err.to_string());
+    }
+
+    #[test]
+    fn jokers_never_downgrade() {
+        let wild_straights = Ruleset {
+            straights: true,
+            ..JOKERS
+        };
+        let straights = &RULESETS[2].1;
+        // the type only depends on which cards are in the hand, not their order
+        for cards in STANDARD.order.chars().combinations_with_replacement(5) {
+            let hand = Hand {
+                cards: cards.try_into().unwrap(),
+            };
+            assert!(
+                JOKERS.hand_type(&hand) >= STANDARD.hand_type(&hand),
+                "{hand:?}"
+            );
+            assert!(
+                wild_straights.hand_type(&hand) >= straights.hand_type(&hand),
+                "{hand:?}"
+            );
+        }
+    }
 }
@@ -1,47 +1,87 @@
-use crate::util::lcm;
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use crate::util::{crt, find_cycle, pattern, Dot, Interner};
+use itertools::Itertools;
 
 pub struct Solution;
 
 impl crate::Solution for Solution {
-    fn solve_1(&self, input: String) -> String {
-        let (dirs, nodes) = parse_input(&input);
+    fn solve_1(&self, input: &str) -> String {
+        let (dirs, network) = parse_input(input);
 
-        let mut pos = "AAA";
+        let mut pos = network.names.id("AAA").unwrap();
+        let end = network.names.id("ZZZ").unwrap();
         let mut i = 0;
         let mut count = 0;
-        while pos != "ZZZ" {
-            let dir = dirs[i];
+        while pos != end {
             count += 1;
-            let (left, right) = &nodes[pos];
-            pos = match dir {
-                Dir::L => left.as_str(),
-                Dir::R => right.as_str(),
-            };
+            pos = network.step(pos, dirs[i]);
             i = (i + 1) % dirs.len();
         }
 
         count.to_string()
     }
 
-    fn solve_2(&self, input: String) -> String {
-        let (dirs, nodes) = parse_input(&input);
+    fn solve_2(&self, input: &str) -> String {
+        let (dirs, network) = parse_input(input);
+
+        let ghosts = network
+            .names
+            .names()
+            .iter()
+            .positions(|it| it.ends_with('A'))
+            .map(|start| ghost_cycle(start, &dirs, &network))
+            .collect_vec();
+        log::info!("{ghosts:?}");
 
-        let starts: HashSet<_> = nodes
-            .keys()
-            .filter(|it| it.ends_with('A'))
-            .map(|it| get_end(it.as_str(), &dirs, &nodes))
-            .collect();
+        first_common_z(&ghosts)
+            .expect("ghosts never line up")
+            .to_string()
+    }
 
-        starts.into_iter().reduce(lcm).unwrap().to_string()
+    fn dot(&self, input: &str) -> Option<Dot> {
+        let (_, network) = parse_input(input);
+        let mut dot = Dot::digraph("network");
+        for (id, name) in network.names.names().iter().enumerate() {
+            let color = if name.ends_with('A') {
+                "green"
+            } else if name.ends_with('Z') {
+                "red"
+            } else {
+                "black"
+            };
+            dot.node(name, &[("color", color)]);
+            let (left, right) = network.next[id];
+            let (left, right) = (network.names.name(left), network.names.name(right));
+            if left == right {
+                dot.edge(name, left, &[("label", "LR")]);
+            } else {
+                dot.edge(name, left, &[("label", "L")]);
+                dot.edge(name, right, &[("label", "R")]);
+            }
+        }
+        Some(dot)
     }
 }
 
-static RE: Lazy<Regex> = Lazy::new(|| Regex::new("(.+) = \\((.+), (.+)\\)").unwrap());
+/**
+The map, with each node name interned to an index into `next`, which holds the left and right
+nodes from each node
+*/
+struct Network<'a> {
+    names: Interner<'a>,
+    next: Vec<(usize, usize)>,
+}
+
+impl Network<'_> {
+    fn step(&self, pos: usize, dir: Dir) -> usize {
+        let (left, right) = self.next[pos];
+        match dir {
+            Dir::L => left,
+            Dir::R => right,
+        }
+    }
+}
 
-fn parse_input(input: &str) -> (Vec<Dir>, HashMap<String, (String, String)>) {
+fn parse_input(input: &str) -> (Vec<Dir>, Network<'_>) {
     let mut lines = input.lines();
     let dirs = lines
         .next()
@@ -56,16 +96,16 @@ fn parse_input(input: &str) -> (Vec<Dir>, HashMap<String, (String, String)>) {
 
     lines.next().unwrap();
 
-    let mut nodes = HashMap::new();
+    let mut names = Interner::new();
+    let mut next = Vec::new();
     for line in lines {
-        let matches = RE.captures(line).unwrap();
-        let name = matches[1].to_string();
-        let left = matches[2].to_string();
-        let right = matches[3].to_string();
-        nodes.insert(name, (left, right));
+        let fields = pattern("{} = ({}, {})", line).unwrap();
+        let [name, left, right] = [0, 1, 2].map(|i| names.intern(fields[i].1.trim()));
+        next.resize(names.len(), (usize::MAX, usize::MAX));
+        next[name] = (left, right);
     }
 
-    (dirs, nodes)
+    (dirs, Network { names, next })
 }
 
 #[derive(Copy, Clone)]
@@ -74,20 +114,135 @@ enum Dir {
     R,
 }
 
-fn get_end(start: &str, dirs: &[Dir], nodes: &HashMap<String, (String, String)>) -> u64 {
-    let mut pos = start;
-    let mut i = 0;
-    let mut count = 0;
-    // input guarantees that you only hit one Z per start
-    while !pos.ends_with('Z') {
-        let dir = dirs[i];
-        count += 1;
-        let (left, right) = &nodes[pos];
-        pos = match dir {
-            Dir::L => left.as_str(),
-            Dir::R => right.as_str(),
+/**
+The steps at which a ghost is on a Z node. After `start` steps the ghost is in a cycle of `length`
+steps, and `z_steps` holds every Z step before `start + length`.
+*/
+#[derive(Debug)]
+struct GhostCycle {
+    start: u64,
+    length: u64,
+    z_steps: Vec<u64>,
+}
+
+impl GhostCycle {
+    fn is_z(&self, step: u64) -> bool {
+        let step = if step < self.start + self.length {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
         };
-        i = (i + 1) % dirs.len();
+        self.z_steps.contains(&step)
+    }
+}
+
+fn ghost_cycle(start: usize, dirs: &[Dir], network: &Network) -> GhostCycle {
+    let cycle = find_cycle(
+        (start, 0),
+        |(pos, i)| (network.step(*pos, dirs[*i]), (i + 1) % dirs.len()),
+        |state| *state,
+    );
+    GhostCycle {
+        start: cycle.period.start as u64,
+        length: cycle.period.length as u64,
+        z_steps: cycle
+            .states()
+            .iter()
+            .positions(|(pos, _)| network.names.name(*pos).ends_with('Z'))
+            .map(|step| step as u64)
+            .collect(),
+    }
+}
+
+fn first_common_z(ghosts: &[GhostCycle]) -> Option<u64> {
+    // before every ghost is in its cycle, where the cycles can't be used to line them up
+    let all_cycling = ghosts.iter().map(|g| g.start).max()?;
+    if let Some(step) = (0..all_cycling).find(|step| ghosts.iter().all(|g| g.is_z(*step))) {
+        return Some(step);
+    }
+
+    // pick one Z step in each ghost's cycle and line them up
+    ghosts
+        .iter()
+        .map(|g| {
+            g.z_steps
+                .iter()
+                .copied()
+                .filter(|s| *s >= g.start)
+                .collect_vec()
+        })
+        .multi_cartesian_product()
+        .filter_map(|steps| {
+            let congruences = steps
+                .iter()
+                .zip(ghosts)
+                .map(|(s, g)| (i64::try_from(*s).unwrap(), i64::try_from(g.length).unwrap()))
+                .collect_vec();
+            let (r, m) = crt(&congruences)?;
+            let (r, m) = (u64::try_from(r).unwrap(), u64::try_from(m).unwrap());
+            let min = steps.into_iter().max().unwrap();
+            Some(if r >= min {
+                r
+            } else {
+                r + (min - r).div_ceil(m) * m
+            })
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Solution;
+
+    #[test]
+    fn ex2() {
+        let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+        assert_eq!("6", super::Solution.solve_2(input));
+    }
+
+    #[test]
+    fn offset_cycles() {
+        // 11A is on Z every 3 steps starting at step 3, 22A every 4 steps starting at step 2
+        let input = "L
+
+11A = (11B, XXX)
+11B = (11C, XXX)
+11C = (11Z, XXX)
+11Z = (11D, XXX)
+11D = (11C, XXX)
+22A = (22B, XXX)
+22B = (22Z, XXX)
+22Z = (22C, XXX)
+22C = (22D, XXX)
+22D = (22E, XXX)
+22E = (22Z, XXX)
+XXX = (XXX, XXX)";
+        assert_eq!("6", super::Solution.solve_2(input));
+    }
+
+    #[test]
+    fn different_lead_ins() {
+        // 11A is on Z at every odd step, and 22A only at step 3, before it loops between 22D and 22E
+        let input = "L
+
+11A = (11Z, XXX)
+11Z = (11A, XXX)
+22A = (22B, XXX)
+22B = (22C, XXX)
+22C = (22Z, XXX)
+22Z = (22D, XXX)
+22D = (22E, XXX)
+22E = (22D, XXX)
+XXX = (XXX, XXX)";
+        assert_eq!("3", super::Solution.solve_2(input));
     }
-    count
 }
@@ -1,6 +1,6 @@
 #![warn(clippy::pedantic)]
 
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use log::Level;
 use std::time::Instant;
 
@@ -77,6 +77,25 @@ fn main() {
                 .value_parser(["trace", "debug", "info", "warn", "error"])
                 .default_value("warn"),
         )
+        .arg(
+            Arg::new("emit_dot")
+                .long("emit-dot")
+                .help("Write the day's graph to dayN.dot instead of solving")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("emit_vcd")
+                .long("emit-vcd")
+                .help("Write a waveform of the day's signals to dayN.vcd instead of solving")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("vcd_signals")
+                .long("vcd-signals")
+                .help("Comma-separated signals to write with --emit-vcd, instead of all of them")
+                .value_delimiter(',')
+                .requires("emit_vcd"),
+        )
         .get_matches();
 
     let level_match = &matches
@@ -99,20 +118,49 @@ fn main() {
 
     let solution = DAYS[day - 1];
 
+    if matches.get_flag("emit_dot") {
+        let input = read_input(day, puzzle.unwrap_or("1"));
+        let Some(dot) = solution.dot(&input) else {
+            eprintln!("Day {day} has no graph");
+            std::process::exit(1);
+        };
+        let path = format!("day{day}.dot");
+        std::fs::write(&path, dot.to_string()).unwrap();
+        println!("Wrote {path}");
+        return;
+    }
+
+    if matches.get_flag("emit_vcd") {
+        let input = read_input(day, puzzle.unwrap_or("1"));
+        let signals: Vec<&str> = matches
+            .get_many::<String>("vcd_signals")
+            .map(|signals| signals.map(String::as_str).collect())
+            .unwrap_or_default();
+        let Some(vcd) = solution.vcd(&input, &signals) else {
+            eprintln!("Day {day} has no waveform");
+            std::process::exit(1);
+        };
+        let path = format!("day{day}.vcd");
+        std::fs::write(&path, vcd).unwrap();
+        println!("Wrote {path}");
+        return;
+    }
+
     let (answer, time) = match puzzle {
         Some("1") => {
             let input = read_input(day, "1");
-            time(|| solution.solve_1(input))
+            time(|| solution.solve_1(&input))
         }
         Some("2") => {
             let input = read_input(day, "2");
-            time(|| solution.solve_2(input))
+            time(|| solution.solve_2(&input))
         }
         Some(_) => unreachable!(),
         None => {
             let input_1 = read_input(day, "1");
             let input_2 = read_input(day, "2");
-            let ((a1, a2), time) = time(|| (solution.solve_1(input_1), solution.solve_2(input_2)));
+            let ((a1, a2), time) =
+                time(|| (solution.solve_1(&input_1), solution.solve_2(&input_2)));
             (a1 + "\n" + &a2, time)
         }
     };
@@ -138,8 +186,23 @@ fn time<T>(f: impl FnOnce() -> T) -> (T, u128) {
 }
 
 trait Solution {
-    fn solve_1(&self, input: String) -> String;
-    fn solve_2(&self, input: String) -> String;
+    fn solve_1(&self, input: &str) -> String;
+    fn solve_2(&self, input: &str) -> String;
+
+    /**
+    The graph the puzzle is built on, for days that have one
+    */
+    fn dot(&self, _input: &str) -> Option<util::Dot> {
+        None
+    }
+
+    /**
+    A waveform of the puzzle's signals in VCD format, for days that have them. If `signals` is not
+    empty, only those signals are wanted.
+    */
+    fn vcd(&self, _input: &str, _signals: &[&str]) -> Option<String> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/**
+The shape of an eventually periodic sequence of states: state `start + length` is the same as state
+`start`, and `length` is as small as possible.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Period {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Period {
+    /**
+    The smallest step whose state is the same as the state at step `n`
+    */
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/**
+Every state from the initial one up to the end of the first repetition of the cycle
+*/
+pub struct Cycle<S> {
+    pub period: Period,
+    states: Vec<S>,
+}
+
+impl<S> Cycle<S> {
+    pub fn nth_state(&self, n: usize) -> &S {
+        &self.states[self.period.reduce(n)]
+    }
+
+    /**
+    States `0..start + length`
+    */
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+}
+
+/**
+Steps from `initial` until a state repeats, where states are the same if their keys are equal. Keeps
+every state, so any later state can be looked up with `Cycle::nth_state`.
+*/
+pub fn find_cycle<S, K: Hash + Eq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    key: impl Fn(&S) -> K,
+) -> Cycle<S> {
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = initial;
+    loop {
+        let k = key(&state);
+        if let Some(start) = seen.get(&k).copied() {
+            log::debug!("state {} repeats state {start}", states.len());
+            let length = states.len() - start;
+            return Cycle {
+                period: Period { start, length },
+                states,
+            };
+        }
+        seen.insert(k, states.len());
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+/**
+Floyd's tortoise and hare. Only keeps two states at a time, at the cost of stepping more often than
+`find_cycle`.
+*/
+pub fn floyd<S: Clone, K: Eq>(
+    initial: &S,
+    step: impl Fn(&S) -> S,
+    key: impl Fn(&S) -> K,
+) -> Period {
+    let mut tortoise = step(initial);
+    let mut hare = step(&tortoise);
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while key(&tortoise) != key(&hare) {
+        hare = step(&hare);
+        length += 1;
+    }
+    Period { start, length }
+}
+
+/**
+Brent's algorithm. Like `floyd` it only keeps two states, but usually steps fewer times.
+*/
+pub fn brent<S: Clone, K: Eq>(
+    initial: &S,
+    step: impl Fn(&S) -> S,
+    key: impl Fn(&S) -> K,
+) -> Period {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while key(&tortoise) != key(&hare) {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Period { start, length }
+}
+
+/**
+The state at step `n`, found by stepping from `initial` at most `period.start + period.length` times
+*/
+pub fn fast_forward<S>(initial: S, step: impl Fn(&S) -> S, period: &Period, n: usize) -> S {
+    let mut state = initial;
+    for _ in 0..period.reduce(n) {
+        state = step(&state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn periods() {
+        let step = |x: &u32| (x * x + 1) % 255;
+        let cycle = find_cycle(3, step, |x| *x);
+        assert_eq!(cycle.period, floyd(&3, step, |x| *x));
+        assert_eq!(cycle.period, brent(&3, step, |x| *x));
+        assert_eq!(
+            Period {
+                start: 2,
+                length: 6
+            },
+            cycle.period
+        );
+
+        let mut x = 3;
+        for n in 0..50 {
+            assert_eq!(x, *cycle.nth_state(n));
+            assert_eq!(x, fast_forward(3, step, &cycle.period, n));
+            x = step(&x);
+        }
+        assert_eq!(
+            *cycle.nth_state(1_000_000_000),
+            fast_forward(3, step, &cycle.period, 1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn pure_cycle() {
+        let step = |x: &u32| (x + 1) % 7;
+        let cycle = find_cycle(0, step, |x| *x);
+        assert_eq!(
+            Period {
+                start: 0,
+                length: 7
+            },
+            cycle.period
+        );
+        assert_eq!(cycle.period, floyd(&0, step, |x| *x));
+        assert_eq!(cycle.period, brent(&0, step, |x| *x));
+        assert_eq!(5, *cycle.nth_state(1_000_000_000 - 1));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::util::{Adjacency, Graph};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+
+/**
+A graph in Graphviz DOT format. Node ids and attribute values are quoted when written, so they can
+contain anything.
+*/
+pub struct Dot {
+    name: String,
+    directed: bool,
+    nodes: Vec<(String, Attrs)>,
+    edges: Vec<(String, String, Attrs)>,
+}
+
+type Attrs = Vec<(String, String)>;
+
+impl Dot {
+    pub fn digraph(name: &str) -> Dot {
+        Dot::new(name, true)
+    }
+
+    pub fn graph(name: &str) -> Dot {
+        Dot::new(name, false)
+    }
+
+    fn new(name: &str, directed: bool) -> Dot {
+        Dot {
+            name: name.to_string(),
+            directed,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /**
+    Declares a node with attributes like `("shape", "box")`. Nodes only used by edges don't need to
+    be declared.
+    */
+    pub fn node(&mut self, id: &str, attrs: &[(&str, &str)]) {
+        self.nodes.push((id.to_string(), owned_attrs(attrs)));
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str, attrs: &[(&str, &str)]) {
+        self.edges
+            .push((from.to_string(), to.to_string(), owned_attrs(attrs)));
+    }
+
+    /**
+    Every node and edge of `graph`, named by `label`. An undirected graph's edges are written once
+    each.
+    */
+    pub fn from_graph<N: Hash + Eq + Clone, E, A: Adjacency<E> + Default>(
+        name: &str,
+        graph: &Graph<N, E, A>,
+        directed: bool,
+        label: impl Fn(&N) -> String,
+    ) -> Dot {
+        let mut dot = Dot::new(name, directed);
+        let labels: Vec<_> = graph.nodes().iter().map(label).collect();
+        for l in &labels {
+            dot.node(l, &[]);
+        }
+        for (a, b, _) in graph.edges() {
+            if directed || a <= b {
+                dot.edge(&labels[a], &labels[b], &[]);
+            }
+        }
+        dot
+    }
+}
+
+fn owned_attrs(attrs: &[(&str, &str)]) -> Attrs {
+    attrs
+        .iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect()
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn write_attrs(f: &mut Formatter<'_>, attrs: &[(String, String)]) -> std::fmt::Result {
+    if attrs.is_empty() {
+        return Ok(());
+    }
+    let attrs: Vec<_> = attrs
+        .iter()
+        .map(|(k, v)| format!("{k}={}", quote(v)))
+        .collect();
+    write!(f, " [{}]", attrs.join(", "))
+}
+
+impl Display for Dot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (keyword, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        writeln!(f, "{keyword} {} {{", quote(&self.name))?;
+        for (id, attrs) in &self.nodes {
+            write!(f, "    {}", quote(id))?;
+            write_attrs(f, attrs)?;
+            writeln!(f, ";")?;
+        }
+        for (from, to, attrs) in &self.edges {
+            write!(f, "    {} {arrow} {}", quote(from), quote(to))?;
+            write_attrs(f, attrs)?;
+            writeln!(f, ";")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write() {
+        let mut dot = Dot::digraph("modules");
+        dot.node("a", &[("shape", "box"), ("label", "%a")]);
+        dot.edge("a", "say \"hi\"", &[("label", "L")]);
+        assert_eq!(
+            "digraph \"modules\" {
+    \"a\" [shape=\"box\", label=\"%a\"];
+    \"a\" -> \"say \\\"hi\\\"\" [label=\"L\"];
+}
+",
+            dot.to_string()
+        );
+
+        let graph: Graph<&str> = Graph::from_lines("a: b c\nb: c", ": ", false);
+        let dot = Dot::from_graph("wires", &graph, false, ToString::to_string);
+        assert_eq!(
+            "graph \"wires\" {
+    \"a\";
+    \"b\";
+    \"c\";
+    \"a\" -- \"b\";
+    \"a\" -- \"c\";
+    \"b\" -- \"c\";
+}
+",
+            dot.to_string()
+        );
+    }
+}
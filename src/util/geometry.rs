@@ -0,0 +1,113 @@
+use crate::util::{gcd, Point};
+
+/**
+Twice the area of the polygon with the given vertices, in order, by the shoelace formula. The
+polygon is closed automatically. Positive if the vertices go counterclockwise in a y-up frame.
+*/
+pub fn signed_double_area(vertices: &[Point<i64>]) -> i64 {
+    edges(vertices).map(|(a, b)| a.0 * b.1 - b.0 * a.1).sum()
+}
+
+/**
+Twice the area of the polygon, so that lattice polygons always have an integer result
+*/
+pub fn double_area(vertices: &[Point<i64>]) -> i64 {
+    signed_double_area(vertices).abs()
+}
+
+/**
+Total length of the edges of a rectilinear polygon
+*/
+pub fn perimeter(vertices: &[Point<i64>]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| {
+            assert!(a.0 == b.0 || a.1 == b.1, "{a} to {b} is not rectilinear");
+            (b.0 - a.0).abs() + (b.1 - a.1).abs()
+        })
+        .sum()
+}
+
+/**
+Number of lattice points on the edges of the polygon
+*/
+pub fn boundary_points(vertices: &[Point<i64>]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| gcd((b.0 - a.0).unsigned_abs(), (b.1 - a.1).unsigned_abs()))
+        .sum::<u64>()
+        .try_into()
+        .unwrap()
+}
+
+/**
+Number of lattice points strictly inside the polygon, by Pick's theorem
+*/
+pub fn interior_points(vertices: &[Point<i64>]) -> i64 {
+    (double_area(vertices) - boundary_points(vertices)) / 2 + 1
+}
+
+/**
+Whether `p` is strictly inside a rectilinear polygon. Points on the boundary are not inside.
+*/
+pub fn point_in_polygon(vertices: &[Point<i64>], p: Point<i64>) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(vertices) {
+        let (x_lo, x_hi) = (a.0.min(b.0), a.0.max(b.0));
+        let (y_lo, y_hi) = (a.1.min(b.1), a.1.max(b.1));
+        if (x_lo..=x_hi).contains(&p.0) && (y_lo..=y_hi).contains(&p.1) {
+            return false;
+        }
+        // cast a ray in the +x direction, counting vertical edges crossed. The half-open y range
+        // makes a ray through a vertex count the two edges that meet there once between them.
+        if a.0 == b.0 && a.0 > p.0 && (y_lo..y_hi).contains(&p.1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn edges(vertices: &[Point<i64>]) -> impl Iterator<Item = (Point<i64>, Point<i64>)> + '_ {
+    vertices
+        .iter()
+        .copied()
+        .zip(vertices.iter().copied().cycle().skip(1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lattice_counts() {
+        // an L shape
+        let vertices = [
+            Point(0, 0),
+            Point(4, 0),
+            Point(4, 2),
+            Point(2, 2),
+            Point(2, 4),
+            Point(0, 4),
+        ];
+        assert_eq!(24, double_area(&vertices));
+        assert_eq!(16, perimeter(&vertices));
+        assert_eq!(16, boundary_points(&vertices));
+        assert_eq!(5, interior_points(&vertices));
+        assert_eq!(
+            -24,
+            signed_double_area(&vertices.iter().rev().copied().collect::<Vec<_>>())
+        );
+
+        let inside = (0..=4)
+            .flat_map(|x| (0..=4).map(move |y| Point(x, y)))
+            .filter(|p| point_in_polygon(&vertices, *p))
+            .count();
+        assert_eq!(5, inside);
+        assert!(point_in_polygon(&vertices, Point(1, 3)));
+        assert!(!point_in_polygon(&vertices, Point(3, 3)));
+        assert!(!point_in_polygon(&vertices, Point(2, 3)));
+
+        let triangle = [Point(0, 0), Point(6, 0), Point(0, 3)];
+        assert_eq!(18, double_area(&triangle));
+        assert_eq!(12, boundary_points(&triangle));
+        assert_eq!(4, interior_points(&triangle));
+    }
+}
@@ -0,0 +1,102 @@
+/**
+Storage for the edges of a `Graph`. Nodes are identified by their index, in insertion order.
+*/
+pub trait Adjacency<E> {
+    fn add_node(&mut self);
+    fn add_edge(&mut self, from: usize, to: usize, edge: E);
+    fn remove_edge(&mut self, from: usize, to: usize) -> Option<E>;
+    fn edge(&self, from: usize, to: usize) -> Option<&E>;
+    fn neighbors<'a>(&'a self, node: usize) -> impl Iterator<Item = (usize, &'a E)>
+    where
+        E: 'a;
+}
+
+/**
+Sparse adjacency, one list of outgoing edges per node. Adding an edge that already exists adds a
+parallel edge.
+*/
+pub struct AdjList<E> {
+    edges: Vec<Vec<(usize, E)>>,
+}
+
+impl<E> Default for AdjList<E> {
+    fn default() -> Self {
+        AdjList { edges: Vec::new() }
+    }
+}
+
+impl<E> Adjacency<E> for AdjList<E> {
+    fn add_node(&mut self) {
+        self.edges.push(Vec::new());
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, edge: E) {
+        self.edges[from].push((to, edge));
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> Option<E> {
+        let list = &mut self.edges[from];
+        let i = list.iter().position(|(n, _)| *n == to)?;
+        Some(list.remove(i).1)
+    }
+
+    fn edge(&self, from: usize, to: usize) -> Option<&E> {
+        self.edges[from]
+            .iter()
+            .find(|(n, _)| *n == to)
+            .map(|(_, e)| e)
+    }
+
+    fn neighbors<'a>(&'a self, node: usize) -> impl Iterator<Item = (usize, &'a E)>
+    where
+        E: 'a,
+    {
+        self.edges[node].iter().map(|(n, e)| (*n, e))
+    }
+}
+
+/**
+Dense adjacency, an n by n matrix of optional edges. Adding an edge that already exists replaces it.
+*/
+pub struct AdjMatrix<E> {
+    matrix: Vec<Vec<Option<E>>>,
+}
+
+impl<E> Default for AdjMatrix<E> {
+    fn default() -> Self {
+        AdjMatrix { matrix: Vec::new() }
+    }
+}
+
+impl<E> Adjacency<E> for AdjMatrix<E> {
+    fn add_node(&mut self) {
+        for row in &mut self.matrix {
+            row.push(None);
+        }
+        let n = self.matrix.len() + 1;
+        self.matrix
+            .push(std::iter::repeat_with(|| None).take(n).collect());
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, edge: E) {
+        self.matrix[from][to] = Some(edge);
+    }
+
+    fn remove_edge(&mut self, from: usize, to: usize) -> Option<E> {
+        self.matrix[from][to].take()
+    }
+
+    fn edge(&self, from: usize, to: usize) -> Option<&E> {
+        self.matrix[from][to].as_ref()
+    }
+
+    fn neighbors<'a>(&'a self, node: usize) -> impl Iterator<Item = (usize, &'a E)>
+    where
+        E: 'a,
+    {
+        self.matrix[node]
+            .iter()
+            .enumerate()
+            .filter_map(|(n, e)| e.as_ref().map(|e| (n, e)))
+    }
+}
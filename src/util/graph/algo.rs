@@ -0,0 +1,266 @@
+use super::{Adjacency, Graph};
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+impl<N: Hash + Eq + Clone, E, A: Adjacency<E> + Default> Graph<N, E, A> {
+    /**
+    Nodes reachable from `start`, in breadth-first order
+    */
+    pub fn bfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut q = VecDeque::new();
+        let mut res = Vec::new();
+        visited[start] = true;
+        q.push_back(start);
+        while let Some(n) = q.pop_front() {
+            res.push(n);
+            for (m, _) in self.neighbors(n) {
+                if !visited[m] {
+                    visited[m] = true;
+                    q.push_back(m);
+                }
+            }
+        }
+        res
+    }
+
+    /**
+    Number of edges on the shortest path from `start` to each node, or `None` if it is unreachable
+    */
+    pub fn bfs_distances(&self, start: usize) -> Vec<Option<usize>> {
+        let mut dists = vec![None; self.len()];
+        let mut q = VecDeque::new();
+        dists[start] = Some(0);
+        q.push_back((start, 0));
+        while let Some((n, d)) = q.pop_front() {
+            for (m, _) in self.neighbors(n) {
+                if dists[m].is_none() {
+                    dists[m] = Some(d + 1);
+                    q.push_back((m, d + 1));
+                }
+            }
+        }
+        dists
+    }
+
+    /**
+    Nodes reachable from `start`, in depth-first preorder
+    */
+    pub fn dfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut stack = vec![start];
+        let mut res = Vec::new();
+        while let Some(n) = stack.pop() {
+            if visited[n] {
+                continue;
+            }
+            visited[n] = true;
+            res.push(n);
+            let mut next: Vec<_> = self
+                .neighbors(n)
+                .map(|(m, _)| m)
+                .filter(|m| !visited[*m])
+                .collect();
+            // so neighbors are visited in order
+            next.reverse();
+            stack.extend(next);
+        }
+        res
+    }
+
+    /**
+    Orders the nodes so that every edge goes from an earlier node to a later one. Returns `None` if
+    the graph has a cycle.
+    */
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        let mut in_degree = vec![0usize; self.len()];
+        for (_, to, _) in self.edges() {
+            in_degree[to] += 1;
+        }
+        let mut q: VecDeque<_> = (0..self.len()).filter(|n| in_degree[*n] == 0).collect();
+        let mut res = Vec::with_capacity(self.len());
+        while let Some(n) = q.pop_front() {
+            res.push(n);
+            for (m, _) in self.neighbors(n) {
+                in_degree[m] -= 1;
+                if in_degree[m] == 0 {
+                    q.push_back(m);
+                }
+            }
+        }
+        (res.len() == self.len()).then_some(res)
+    }
+
+    /**
+    Tarjan's algorithm. Components are returned in reverse topological order, so no component has
+    an edge to a component after it.
+    */
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let n = self.len();
+        let mut index = vec![UNVISITED; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut res = Vec::new();
+
+        for root in 0..n {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            // (node, neighbors of node still to look at)
+            let mut call_stack = vec![(
+                root,
+                self.neighbors(root).map(|(m, _)| m).collect::<Vec<_>>(),
+            )];
+            index[root] = next_index;
+            low[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((v, remaining)) = call_stack.last_mut() {
+                let v = *v;
+                if let Some(w) = remaining.pop() {
+                    if index[w] == UNVISITED {
+                        index[w] = next_index;
+                        low[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        call_stack.push((w, self.neighbors(w).map(|(m, _)| m).collect()));
+                    } else if on_stack[w] {
+                        low[v] = low[v].min(index[w]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some((parent, _)) = call_stack.last() {
+                    low[*parent] = low[*parent].min(low[v]);
+                }
+                if low[v] == index[v] {
+                    let mut component = Vec::new();
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w] = false;
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    res.push(component);
+                }
+            }
+        }
+
+        res
+    }
+
+    /**
+    Components of the graph when edge direction is ignored
+    */
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut parent: Vec<_> = (0..self.len()).collect();
+        for (a, b, _) in self.edges() {
+            let ra = find(&mut parent, a);
+            let rb = find(&mut parent, b);
+            parent[ra] = rb;
+        }
+
+        let mut component_of = vec![usize::MAX; self.len()];
+        let mut res: Vec<Vec<usize>> = Vec::new();
+        for n in 0..self.len() {
+            let root = find(&mut parent, n);
+            if component_of[root] == usize::MAX {
+                component_of[root] = res.len();
+                res.push(Vec::new());
+            }
+            res[component_of[root]].push(n);
+        }
+        res
+    }
+
+    /**
+    The immediate dominator of each node reachable from `root`: the closest node other than itself
+    that every path from `root` to it goes through. `root` is its own immediate dominator and
+    unreachable nodes have none. Cooper, Harvey and Kennedy's iterative algorithm.
+    */
+    pub fn immediate_dominators(&self, root: usize) -> Vec<Option<usize>> {
+        let n = self.len();
+        let mut visited = vec![false; n];
+        let mut postorder = Vec::with_capacity(n);
+        let mut call_stack = vec![(
+            root,
+            self.neighbors(root).map(|(m, _)| m).collect::<Vec<_>>(),
+        )];
+        visited[root] = true;
+        while let Some((v, remaining)) = call_stack.last_mut() {
+            let v = *v;
+            if let Some(w) = remaining.pop() {
+                if !visited[w] {
+                    visited[w] = true;
+                    call_stack.push((w, self.neighbors(w).map(|(m, _)| m).collect()));
+                }
+            } else {
+                postorder.push(v);
+                call_stack.pop();
+            }
+        }
+        let mut position = vec![0; n];
+        for (i, v) in postorder.iter().enumerate() {
+            position[*v] = i;
+        }
+        let mut preds = vec![Vec::new(); n];
+        for (a, b, _) in self.edges() {
+            if visited[a] {
+                preds[b].push(a);
+            }
+        }
+
+        let mut idom = vec![None; n];
+        idom[root] = Some(root);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            // reverse postorder, skipping the root at the end of the postorder
+            for &v in postorder.iter().rev().skip(1) {
+                let mut new_idom = None;
+                for &p in &preds[v] {
+                    if idom[p].is_some() {
+                        new_idom = Some(new_idom.map_or(p, |d| intersect(&idom, &position, p, d)));
+                    }
+                }
+                if new_idom != idom[v] {
+                    idom[v] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+        idom
+    }
+}
+
+// the closest common dominator of `a` and `b`, walking up the dominators found so far
+fn intersect(idom: &[Option<usize>], position: &[usize], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        while position[a] < position[b] {
+            a = idom[a].unwrap();
+        }
+        while position[b] < position[a] {
+            b = idom[b].unwrap();
+        }
+    }
+    a
+}
+
+// union-find root with path halving
+fn find(parent: &mut [usize], mut n: usize) -> usize {
+    while parent[n] != n {
+        parent[n] = parent[parent[n]];
+        n = parent[n];
+    }
+    n
+}
@@ -0,0 +1,350 @@
+use super::{Adjacency, Cut, Graph};
+use priority_queue::PriorityQueue;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+impl<N: Hash + Eq + Clone, E, A: Adjacency<E> + Default> Graph<N, E, A> {
+    /**
+    Global minimum cut using Stoer-Wagner. The graph is treated as undirected: an edge in either
+    direction connects its nodes, and an edge added in both directions is only counted once.
+    Parallel edges each count, so a pair of nodes linked twice in each direction is linked by two
+    edges.
+    */
+    pub fn min_cut(&self, weight: impl Fn(&E) -> u64) -> Cut {
+        assert!(
+            self.len() >= 2,
+            "cannot cut a graph with fewer than 2 nodes"
+        );
+
+        // the weights of the edges between each pair of nodes, going up and going down
+        let mut directed: HashMap<(usize, usize), [Vec<u64>; 2]> = HashMap::new();
+        for (a, b, e) in self.edges() {
+            if a == b {
+                continue;
+            }
+            let dir = usize::from(a > b);
+            directed.entry((a.min(b), a.max(b))).or_default()[dir].push(weight(e));
+        }
+        // the kth edge going up and the kth going down are taken to be the same undirected edge
+        let undirected: Vec<(usize, usize, u64)> = directed
+            .into_iter()
+            .flat_map(|((a, b), [up, down])| {
+                let n = up.len().max(down.len());
+                (0..n).map(move |k| (a, b, up.get(k).or(down.get(k)).copied().unwrap()))
+            })
+            .collect();
+
+        let mut adj: Vec<HashMap<usize, u64>> = vec![HashMap::new(); self.len()];
+        for &(a, b, w) in &undirected {
+            *adj[a].entry(b).or_default() += w;
+            *adj[b].entry(a).or_default() += w;
+        }
+
+        let mut members: Vec<Vec<usize>> = (0..self.len()).map(|n| vec![n]).collect();
+        let mut active = vec![true; self.len()];
+        let mut best: Option<(u64, Vec<usize>)> = None;
+
+        for _ in 1..self.len() {
+            // maximum adjacency ordering: repeatedly add the node most tightly connected to the
+            // nodes already added
+            let mut q: PriorityQueue<usize, u64> = (0..self.len())
+                .filter(|n| active[*n])
+                .map(|n| (n, 0))
+                .collect();
+            let mut added = vec![false; self.len()];
+            let mut s = None;
+            let mut t = None;
+            let mut cut_of_phase = 0;
+            while let Some((v, w)) = q.pop() {
+                added[v] = true;
+                s = t;
+                t = Some(v);
+                cut_of_phase = w;
+                for (u, wu) in &adj[v] {
+                    if !added[*u] {
+                        q.change_priority_by(u, |p| *p += wu);
+                    }
+                }
+            }
+            let (Some(s), Some(t)) = (s, t) else {
+                unreachable!()
+            };
+
+            log::debug!("phase cut {cut_of_phase} between {s} and {t}");
+            if best.as_ref().is_none_or(|(w, _)| cut_of_phase < *w) {
+                best = Some((cut_of_phase, members[t].clone()));
+            }
+
+            // merge t into s
+            let t_members = std::mem::take(&mut members[t]);
+            members[s].extend(t_members);
+            active[t] = false;
+            for (u, w) in std::mem::take(&mut adj[t]) {
+                adj[u].remove(&t);
+                if u != s {
+                    *adj[s].entry(u).or_default() += w;
+                    *adj[u].entry(s).or_default() += w;
+                }
+            }
+        }
+
+        let (weight, side_members) = best.unwrap();
+        let mut in_side = vec![false; self.len()];
+        for n in side_members {
+            in_side[n] = true;
+        }
+        let (side, other) = (0..self.len()).partition(|n| in_side[*n]);
+        let mut edges: Vec<_> = undirected
+            .into_iter()
+            .filter(|(a, b, _)| in_side[*a] != in_side[*b])
+            .map(|(a, b, _)| (a, b))
+            .collect();
+        edges.sort_unstable();
+        Cut {
+            weight,
+            edges,
+            side,
+            other,
+        }
+    }
+
+    /**
+    Maximum flow from `s` to `t` using Edmonds-Karp. The returned cut is the minimum `s`-`t` cut: `side`
+    holds the nodes still reachable from `s` in the residual network, and its weight is the flow.
+    */
+    pub fn max_flow_edmonds_karp(&self, s: usize, t: usize, capacity: impl Fn(&E) -> u64) -> Cut {
+        let mut net = FlowNetwork::new(self, capacity);
+        let mut flow = 0;
+        loop {
+            // BFS for the shortest augmenting path, remembering the edge used to reach each node
+            let mut via = vec![None; net.adj.len()];
+            let mut q = VecDeque::new();
+            q.push_back(s);
+            while let Some(n) = q.pop_front() {
+                if n == t {
+                    break;
+                }
+                for &e in &net.adj[n] {
+                    let m = net.to[e];
+                    if net.cap[e] > 0 && m != s && via[m].is_none() {
+                        via[m] = Some(e);
+                        q.push_back(m);
+                    }
+                }
+            }
+            if via[t].is_none() {
+                break;
+            }
+
+            let mut path = Vec::new();
+            let mut n = t;
+            while let Some(e) = via[n] {
+                path.push(e);
+                n = net.to[e ^ 1];
+            }
+            let bottleneck = path.iter().map(|e| net.cap[*e]).min().unwrap();
+            for e in path {
+                net.cap[e] -= bottleneck;
+                net.cap[e ^ 1] += bottleneck;
+            }
+            flow += bottleneck;
+        }
+        Cut::from_side(self, flow, &net.reachable(s))
+    }
+
+    /**
+    Maximum flow from `s` to `t` using Dinic's algorithm. The returned cut is the same as for
+    `max_flow_edmonds_karp`.
+    */
+    pub fn max_flow_dinic(&self, s: usize, t: usize, capacity: impl Fn(&E) -> u64) -> Cut {
+        let mut net = FlowNetwork::new(self, capacity);
+        let mut flow = 0;
+        loop {
+            let mut level = vec![None; net.adj.len()];
+            level[s] = Some(0);
+            let mut q = VecDeque::new();
+            q.push_back(s);
+            while let Some(n) = q.pop_front() {
+                for &e in &net.adj[n] {
+                    let m = net.to[e];
+                    if net.cap[e] > 0 && level[m].is_none() {
+                        level[m] = Some(level[n].unwrap() + 1);
+                        q.push_back(m);
+                    }
+                }
+            }
+            if level[t].is_none() {
+                break;
+            }
+
+            let mut next_edge = vec![0; net.adj.len()];
+            loop {
+                let pushed = net.blocking_flow(s, t, u64::MAX, &level, &mut next_edge);
+                if pushed == 0 {
+                    break;
+                }
+                flow += pushed;
+            }
+        }
+        Cut::from_side(self, flow, &net.reachable(s))
+    }
+}
+
+/**
+Residual network. Edges are stored in pairs, so the reverse of edge `e` is `e ^ 1`.
+*/
+struct FlowNetwork {
+    adj: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    cap: Vec<u64>,
+}
+
+impl FlowNetwork {
+    fn new<N: Hash + Eq + Clone, E, A: Adjacency<E> + Default>(
+        graph: &Graph<N, E, A>,
+        capacity: impl Fn(&E) -> u64,
+    ) -> FlowNetwork {
+        let mut net = FlowNetwork {
+            adj: vec![Vec::new(); graph.len()],
+            to: Vec::new(),
+            cap: Vec::new(),
+        };
+        for (a, b, e) in graph.edges() {
+            net.adj[a].push(net.to.len());
+            net.to.push(b);
+            net.cap.push(capacity(e));
+            net.adj[b].push(net.to.len());
+            net.to.push(a);
+            net.cap.push(0);
+        }
+        net
+    }
+
+    fn blocking_flow(
+        &mut self,
+        n: usize,
+        t: usize,
+        limit: u64,
+        level: &[Option<usize>],
+        next_edge: &mut [usize],
+    ) -> u64 {
+        if n == t {
+            return limit;
+        }
+        while next_edge[n] < self.adj[n].len() {
+            let e = self.adj[n][next_edge[n]];
+            let m = self.to[e];
+            if self.cap[e] > 0 && level[m] == level[n].map(|l| l + 1) {
+                let pushed = self.blocking_flow(m, t, limit.min(self.cap[e]), level, next_edge);
+                if pushed > 0 {
+                    self.cap[e] -= pushed;
+                    self.cap[e ^ 1] += pushed;
+                    return pushed;
+                }
+            }
+            next_edge[n] += 1;
+        }
+        0
+    }
+
+    fn reachable(&self, s: usize) -> Vec<bool> {
+        let mut visited = vec![false; self.adj.len()];
+        let mut q = vec![s];
+        while let Some(n) = q.pop() {
+            if std::mem::replace(&mut visited[n], true) {
+                continue;
+            }
+            q.extend(
+                self.adj[n]
+                    .iter()
+                    .filter(|e| self.cap[**e] > 0)
+                    .map(|e| self.to[*e]),
+            );
+        }
+        visited
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::Graph;
+
+    const EX: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    fn cut_names(graph: &Graph<&str>, edges: &[(usize, usize)]) -> Vec<(String, String)> {
+        let mut names: Vec<_> = edges
+            .iter()
+            .map(|(a, b)| {
+                let (a, b) = (graph.node(*a), graph.node(*b));
+                if a < b {
+                    (a.to_string(), b.to_string())
+                } else {
+                    (b.to_string(), a.to_string())
+                }
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn min_cuts() {
+        let graph: Graph<&str> = Graph::from_lines(EX, ": ", false);
+        let expected = vec![
+            ("bvb".to_string(), "cmg".to_string()),
+            ("hfx".to_string(), "pzl".to_string()),
+            ("jqt".to_string(), "nvd".to_string()),
+        ];
+
+        let cut = graph.min_cut(|()| 1);
+        assert_eq!(3, cut.weight);
+        assert_eq!(54, cut.side.len() * cut.other.len());
+        assert_eq!(expected, cut_names(&graph, &cut.edges));
+
+        let s = graph.index_of(&"jqt").unwrap();
+        let t = graph.index_of(&"rsh").unwrap();
+        for cut in [
+            graph.max_flow_edmonds_karp(s, t, |()| 1),
+            graph.max_flow_dinic(s, t, |()| 1),
+        ] {
+            assert_eq!(3, cut.weight);
+            assert!(cut.side.contains(&s));
+            assert!(cut.other.contains(&t));
+            assert_eq!(expected, cut_names(&graph, &cut.edges));
+        }
+    }
+
+    #[test]
+    fn parallel_edges() {
+        let mut graph: Graph<&str> = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_undirected_edge(a, b, ());
+        graph.add_undirected_edge(a, b, ());
+        graph.add_edge(c, a, ());
+        let cut = graph.min_cut(|()| 1);
+        assert_eq!(1, cut.weight);
+        assert_eq!(vec![(a, c)], cut.edges);
+
+        // a second edge from c to a, even though it only goes one way, is a second edge between them
+        graph.add_edge(c, a, ());
+        graph.add_undirected_edge(a, b, ());
+        let cut = graph.min_cut(|()| 1);
+        assert_eq!(2, cut.weight);
+        assert_eq!(vec![(a, c), (a, c)], cut.edges);
+        assert!(cut.side == vec![c] || cut.other == vec![c]);
+    }
+}
@@ -0,0 +1,264 @@
+mod adjacency;
+mod algo;
+mod cut;
+
+pub use adjacency::*;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/**
+A directed graph whose nodes are interned: each distinct `N` is stored once and identified by its
+index, in insertion order. Undirected graphs are represented by adding each edge in both directions.
+*/
+pub struct Graph<N, E = (), A = AdjList<E>> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    adj: A,
+    _edge: PhantomData<E>,
+}
+
+impl<N, E, A: Default> Default for Graph<N, E, A> {
+    fn default() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            adj: A::default(),
+            _edge: PhantomData,
+        }
+    }
+}
+
+impl<N, E, A> Graph<N, E, A> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, i: usize) -> &N {
+        &self.nodes[i]
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+}
+
+impl<N: Hash + Eq + Clone, E, A: Adjacency<E> + Default> Graph<N, E, A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Returns the index of `node`, adding it to the graph if it isn't already present
+    */
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(i) = self.index.get(&node) {
+            return *i;
+        }
+        let i = self.nodes.len();
+        self.index.insert(node.clone(), i);
+        self.nodes.push(node);
+        self.adj.add_node();
+        i
+    }
+
+    pub fn index_of(&self, node: &N) -> Option<usize> {
+        self.index.get(node).copied()
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, edge: E) {
+        self.adj.add_edge(from, to, edge);
+    }
+
+    pub fn add_undirected_edge(&mut self, a: usize, b: usize, edge: E)
+    where
+        E: Clone,
+    {
+        self.adj.add_edge(a, b, edge.clone());
+        self.adj.add_edge(b, a, edge);
+    }
+
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> Option<E> {
+        self.adj.remove_edge(from, to)
+    }
+
+    pub fn remove_undirected_edge(&mut self, a: usize, b: usize) -> Option<E> {
+        self.adj.remove_edge(b, a);
+        self.adj.remove_edge(a, b)
+    }
+
+    pub fn edge(&self, from: usize, to: usize) -> Option<&E> {
+        self.adj.edge(from, to)
+    }
+
+    pub fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, &E)> {
+        self.adj.neighbors(node)
+    }
+
+    /**
+    All edges as `(from, to, edge)`. Undirected edges appear once in each direction.
+    */
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, &E)> {
+        (0..self.len()).flat_map(move |a| self.neighbors(a).map(move |(b, e)| (a, b, e)))
+    }
+}
+
+impl<'a, A: Adjacency<()> + Default> Graph<&'a str, (), A> {
+    /**
+    Builds a graph from lines like `name: a b c`, where `sep` separates the node name from the
+    names it links to. The linked names may be separated by whitespace and/or commas.
+    */
+    pub fn from_lines(input: &'a str, sep: &str, directed: bool) -> Self {
+        let mut graph = Self::new();
+        for line in input.lines().filter(|line| !line.is_empty()) {
+            let (name, dests) = line
+                .split_once(sep)
+                .unwrap_or_else(|| panic!("no {sep:?} in {line:?}"));
+            let a = graph.add_node(name.trim());
+            for dest in dests
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|d| !d.is_empty())
+            {
+                let b = graph.add_node(dest);
+                if directed {
+                    graph.add_edge(a, b, ());
+                } else {
+                    graph.add_undirected_edge(a, b, ());
+                }
+            }
+        }
+        graph
+    }
+}
+
+/**
+A partition of the nodes of a graph into two sides, along with the edges that cross from `side` to
+`other` and their total weight.
+*/
+#[derive(Debug)]
+pub struct Cut {
+    pub weight: u64,
+    pub edges: Vec<(usize, usize)>,
+    pub side: Vec<usize>,
+    pub other: Vec<usize>,
+}
+
+impl Cut {
+    fn from_side<N: Hash + Eq + Clone, E, A: Adjacency<E> + Default>(
+        graph: &Graph<N, E, A>,
+        weight: u64,
+        in_side: &[bool],
+    ) -> Cut {
+        let (side, other) = (0..graph.len()).partition(|n| in_side[*n]);
+        let edges = graph
+            .edges()
+            .filter(|(a, b, _)| in_side[*a] && !in_side[*b])
+            .map(|(a, b, _)| (a, b))
+            .collect();
+        Cut {
+            weight,
+            edges,
+            side,
+            other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EX: &str = "a: b
+b: c e f
+c: d g
+d: c h
+e: a f
+f: g
+g: f
+h: d g";
+
+    #[test]
+    fn from_lines() {
+        let graph: Graph<&str> = Graph::from_lines(EX, ": ", true);
+        assert_eq!(8, graph.len());
+        assert_eq!(vec!["a", "b", "c", "e", "f", "d", "g", "h"], graph.nodes());
+        let b = graph.index_of(&"b").unwrap();
+        let e = graph.index_of(&"e").unwrap();
+        assert!(graph.edge(b, e).is_some());
+        assert!(graph.edge(e, b).is_none());
+
+        let undirected: Graph<&str, (), AdjMatrix<()>> = Graph::from_lines(EX, ": ", false);
+        assert!(undirected.edge(e, b).is_some());
+    }
+
+    #[test]
+    fn sccs() {
+        let graph: Graph<&str> = Graph::from_lines(EX, ": ", true);
+        let mut sccs: Vec<Vec<&str>> = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|scc| {
+                let mut names: Vec<_> = scc.into_iter().map(|i| *graph.node(i)).collect();
+                names.sort_unstable();
+                names
+            })
+            .collect();
+        // sinks come first
+        assert_eq!(vec!["f", "g"], sccs[0]);
+        sccs.sort();
+        assert_eq!(
+            vec![vec!["a", "b", "e"], vec!["c", "d", "h"], vec!["f", "g"]],
+            sccs
+        );
+        assert!(graph.topological_sort().is_none());
+        assert_eq!(1, graph.connected_components().len());
+    }
+
+    #[test]
+    fn topological_sort() {
+        let graph: Graph<&str> = Graph::from_lines("a: b c\nb: d\nc: d\ne: f", ": ", true);
+        let order = graph.topological_sort().unwrap();
+        let pos = |n: &str| order.iter().position(|i| graph.node(*i) == &n).unwrap();
+        assert!(pos("a") < pos("b"));
+        assert!(pos("a") < pos("c"));
+        assert!(pos("b") < pos("d"));
+        assert!(pos("c") < pos("d"));
+        assert!(pos("e") < pos("f"));
+        assert_eq!(2, graph.connected_components().len());
+        assert_eq!(4, graph.bfs(0).len());
+        assert_eq!(
+            vec![Some(0), Some(1), Some(1), Some(2)],
+            graph.bfs_distances(0)[..4]
+        );
+    }
+
+    #[test]
+    fn dominators() {
+        let graph: Graph<&str> = Graph::from_lines(EX, ": ", true);
+        let idom = graph.immediate_dominators(0);
+        let idom_of = |n: &str| idom[graph.index_of(&n).unwrap()].map(|d| *graph.node(d));
+        assert_eq!(Some("a"), idom_of("a"));
+        assert_eq!(Some("a"), idom_of("b"));
+        for n in ["c", "e", "f", "g"] {
+            assert_eq!(Some("b"), idom_of(n), "{n}");
+        }
+        assert_eq!(Some("c"), idom_of("d"));
+        assert_eq!(Some("d"), idom_of("h"));
+
+        let graph: Graph<&str> = Graph::from_lines("a: b c\nb: d\nc: d\ne: f", ": ", true);
+        let idom: Vec<_> = graph
+            .immediate_dominators(0)
+            .into_iter()
+            .map(|d| d.map(|d| *graph.node(d)))
+            .collect();
+        assert_eq!(
+            vec![Some("a"), Some("a"), Some("a"), Some("a"), None, None],
+            idom
+        );
+    }
+}
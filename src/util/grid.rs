@@ -3,6 +3,7 @@ use std::fmt::{Debug, Formatter};
 use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
+#[derive(Clone)]
 pub struct Grid<T> {
     data: Vec<Vec<T>>,
 }
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+/**
+Assigns each distinct name a dense id, in the order they are first seen, without copying the names
+*/
+#[derive(Default, Debug)]
+pub struct Interner<'a> {
+    ids: HashMap<&'a str, usize>,
+    names: Vec<&'a str>,
+}
+
+impl<'a> Interner<'a> {
+    pub fn new() -> Interner<'a> {
+        Interner::default()
+    }
+
+    pub fn intern(&mut self, name: &'a str) -> usize {
+        *self.ids.entry(name).or_insert_with(|| {
+            self.names.push(name);
+            self.names.len() - 1
+        })
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &'a str {
+        self.names[id]
+    }
+
+    pub fn names(&self) -> &[&'a str] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let mut names = Interner::new();
+        assert_eq!(0, names.intern("a"));
+        assert_eq!(1, names.intern("b"));
+        assert_eq!(0, names.intern("a"));
+        assert_eq!(Some(1), names.id("b"));
+        assert_eq!(None, names.id("c"));
+        assert_eq!("b", names.name(1));
+        assert_eq!(2, names.len());
+    }
+}
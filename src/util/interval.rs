@@ -0,0 +1,378 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{Add, Range, Sub};
+
+/**
+A set of values stored as sorted, disjoint, non-adjacent half-open ranges
+*/
+#[derive(Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        // ranges that overlap or touch the new one get merged into it
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if first < last {
+            range.start.min(self.ranges[first].start)..range.end.max(self.ranges[last - 1].end)
+        } else {
+            range
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= *value);
+        self.ranges.get(i).is_some_and(|r| r.contains(value))
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.ranges.iter().chain(&other.ranges).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for r in &self.ranges {
+            let mut start = r.start;
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while start < r.end && k < other.ranges.len() && other.ranges[k].start < r.end {
+                let cut = &other.ranges[k];
+                if start < cut.start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+            if start < r.end {
+                ranges.push(start..r.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /**
+    Splits into the values less than `at` and the values at least `at`
+    */
+    pub fn split_at(&self, at: T) -> (IntervalSet<T>, IntervalSet<T>) {
+        let mut below = Vec::new();
+        let mut above = Vec::new();
+        for r in &self.ranges {
+            if r.end <= at {
+                below.push(r.clone());
+            } else if r.start >= at {
+                above.push(r.clone());
+            } else {
+                below.push(r.start..at);
+                above.push(at..r.end);
+            }
+        }
+        (IntervalSet { ranges: below }, IntervalSet { ranges: above })
+    }
+}
+
+impl<T: Copy + Ord + Sub<Output = T> + Add<Output = T> + Default> IntervalSet<T> {
+    /**
+    The number of values in the set
+    */
+    pub fn total_len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::default(), |acc, r| acc + (r.end - r.start))
+    }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = IntervalSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl<T: Debug> Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(&self.ranges).finish()
+    }
+}
+
+/**
+A value for each of a set of sorted, disjoint half-open ranges. Inserting a range replaces the values
+of anything it overlaps.
+*/
+#[derive(Clone, PartialEq, Eq)]
+pub struct IntervalMap<T, V> {
+    entries: Vec<(Range<T>, V)>,
+}
+
+impl<T, V> IntervalMap<T, V> {
+    pub fn new() -> IntervalMap<T, V> {
+        IntervalMap {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[(Range<T>, V)] {
+        &self.entries
+    }
+}
+
+impl<T, V> Default for IntervalMap<T, V> {
+    fn default() -> Self {
+        IntervalMap::new()
+    }
+}
+
+impl<T: Copy + Ord, V: Clone> IntervalMap<T, V> {
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self.entries.partition_point(|(r, _)| r.end <= range.start);
+        let last = self.entries.partition_point(|(r, _)| r.start < range.end);
+        let mut replacement = Vec::with_capacity(3);
+        if let Some((r, v)) = self.entries.get(first).filter(|_| first < last) {
+            if r.start < range.start {
+                replacement.push((r.start..range.start, v.clone()));
+            }
+        }
+        replacement.push((range.clone(), value));
+        if let Some((r, v)) = last
+            .checked_sub(1)
+            .filter(|l| first <= *l)
+            .map(|l| &self.entries[l])
+        {
+            if r.end > range.end {
+                replacement.push((range.end..r.end, v.clone()));
+            }
+        }
+        self.entries.splice(first..last, replacement);
+    }
+
+    pub fn get(&self, key: &T) -> Option<&V> {
+        let i = self.entries.partition_point(|(r, _)| r.end <= *key);
+        self.entries
+            .get(i)
+            .filter(|(r, _)| r.contains(key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn domain(&self) -> IntervalSet<T> {
+        self.entries.iter().map(|(r, _)| r.clone()).collect()
+    }
+
+    /**
+    Splits `range` at the boundaries of the entries, pairing each piece with its value, or `None` for
+    the gaps between entries
+    */
+    pub fn pieces(&self, range: Range<T>) -> Vec<(Range<T>, Option<&V>)> {
+        let mut res = Vec::new();
+        let mut start = range.start;
+        let first = self.entries.partition_point(|(r, _)| r.end <= range.start);
+        for (r, v) in &self.entries[first..] {
+            if start >= range.end || r.start >= range.end {
+                break;
+            }
+            if start < r.start {
+                res.push((start..r.start, None));
+                start = r.start;
+            }
+            let end = r.end.min(range.end);
+            res.push((start..end, Some(v)));
+            start = end;
+        }
+        if start < range.end {
+            res.push((start..range.end, None));
+        }
+        res
+    }
+}
+
+/**
+Piecewise offset mappings, where a value in an entry's range maps to itself plus the entry's value,
+and a value in no entry maps to itself
+*/
+impl<N: Copy + Ord + Add<Output = N> + Sub<Output = N> + Default> IntervalMap<N, N> {
+    pub fn apply(&self, x: N) -> N {
+        x + self.get(&x).copied().unwrap_or_default()
+    }
+
+    /**
+    The image of every value in `set`
+    */
+    pub fn apply_set(&self, set: &IntervalSet<N>) -> IntervalSet<N> {
+        set.ranges()
+            .iter()
+            .flat_map(|r| self.pieces(r.clone()))
+            .map(|(r, offset)| {
+                let offset = offset.copied().unwrap_or_default();
+                (r.start + offset)..(r.end + offset)
+            })
+            .collect()
+    }
+
+    /**
+    The mapping that applies `self` and then `later`
+    */
+    pub fn compose(&self, later: &IntervalMap<N, N>) -> IntervalMap<N, N> {
+        let mut res = IntervalMap::new();
+        for (r, offset) in &self.entries {
+            let image = (r.start + *offset)..(r.end + *offset);
+            for (piece, later_offset) in later.pieces(image) {
+                let total = *offset + later_offset.copied().unwrap_or_default();
+                if total != N::default() {
+                    res.insert((piece.start - *offset)..(piece.end - *offset), total);
+                }
+            }
+        }
+        let domain = self.domain();
+        for (r, offset) in &later.entries {
+            for piece in IntervalSet::from(r.clone()).difference(&domain).ranges() {
+                res.insert(piece.clone(), *offset);
+            }
+        }
+        res
+    }
+}
+
+impl<T: Debug, V: Debug> Debug for IntervalMap<T, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(r, v)| (r, v)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_ops() {
+        let a: IntervalSet<i32> = [0..5, 10..15, 4..7].into_iter().collect();
+        assert_eq!(&[0..7, 10..15], a.ranges());
+        let b: IntervalSet<i32> = [3..11, 14..20].into_iter().collect();
+
+        assert_eq!(IntervalSet::from(0..20), a.union(&b));
+        assert_eq!(&[3..7, 10..11, 14..15], a.intersection(&b).ranges());
+        assert_eq!(&[0..3, 11..14], a.difference(&b).ranges());
+        assert_eq!(&[7..10, 15..20], b.difference(&a).ranges());
+        assert_eq!(12, a.total_len());
+        assert!(a.contains(&6));
+        assert!(!a.contains(&7));
+
+        let (below, above) = a.split_at(12);
+        assert_eq!(&[0..7, 10..12], below.ranges());
+        assert_eq!(IntervalSet::from(12..15), above);
+
+        let mut c = a.clone();
+        c.insert(7..10);
+        assert_eq!(IntervalSet::from(0..15), c);
+    }
+
+    #[test]
+    fn map_insert() {
+        let mut m = IntervalMap::new();
+        m.insert(0..10, 'a');
+        m.insert(20..30, 'b');
+        m.insert(5..25, 'c');
+        assert_eq!(&[(0..5, 'a'), (5..25, 'c'), (25..30, 'b')], m.entries());
+        m.insert(10..12, 'd');
+        assert_eq!(
+            &[
+                (0..5, 'a'),
+                (5..10, 'c'),
+                (10..12, 'd'),
+                (12..25, 'c'),
+                (25..30, 'b')
+            ],
+            m.entries()
+        );
+        assert_eq!(Some(&'d'), m.get(&11));
+        assert_eq!(None, m.get(&30));
+    }
+
+    #[test]
+    fn compose() {
+        let mut earlier = IntervalMap::new();
+        earlier.insert(0..69, 10);
+        earlier.insert(69..79, -69);
+        let mut later = IntervalMap::new();
+        later.insert(56..93, 4);
+        later.insert(93..97, -37);
+
+        let both = earlier.compose(&later);
+        assert_eq!(
+            &[
+                (0..46, 10),
+                (46..69, 14),
+                (69..79, -69),
+                (79..93, 4),
+                (93..97, -37)
+            ],
+            both.entries()
+        );
+        for x in -5..105 {
+            assert_eq!(later.apply(earlier.apply(x)), both.apply(x));
+        }
+
+        let image = both.apply_set(&IntervalSet::from(40..80));
+        assert_eq!(&[0..10, 50..56, 60..84], image.ranges());
+    }
+}
@@ -0,0 +1,80 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+pub fn rational(n: impl Into<BigInt>) -> BigRational {
+    BigRational::from_integer(n.into())
+}
+
+/**
+Reduces `matrix` to reduced row echelon form in place using exact Gaussian elimination. Returns the
+pivot column of each non-zero row, so the length is the rank.
+*/
+pub fn row_reduce(matrix: &mut [Vec<BigRational>]) -> Vec<usize> {
+    let cols = matrix.first().map_or(0, Vec::len);
+    let mut pivots = Vec::new();
+    let mut row = 0;
+    for col in 0..cols {
+        if row == matrix.len() {
+            break;
+        }
+        let Some(pivot_row) = (row..matrix.len()).find(|r| !matrix[*r][col].is_zero()) else {
+            continue;
+        };
+        matrix.swap(row, pivot_row);
+
+        let pivot = matrix[row][col].clone();
+        for v in &mut matrix[row] {
+            *v /= &pivot;
+        }
+        let pivot_row = matrix[row].clone();
+        for (r, other) in matrix.iter_mut().enumerate() {
+            if r == row || other[col].is_zero() {
+                continue;
+            }
+            let factor = other[col].clone();
+            for (v, p) in other.iter_mut().zip(&pivot_row).skip(col) {
+                *v -= &factor * p;
+            }
+        }
+
+        pivots.push(col);
+        row += 1;
+    }
+    pivots
+}
+
+/**
+Solves `a x = b` exactly. Returns `None` unless there is exactly one solution.
+*/
+pub fn solve_linear(a: &[Vec<BigRational>], b: &[BigRational]) -> Option<Vec<BigRational>> {
+    assert_eq!(a.len(), b.len(), "one right hand side per equation");
+    let unknowns = a.first().map_or(0, Vec::len);
+    let mut augmented: Vec<Vec<BigRational>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, rhs)| {
+            assert_eq!(unknowns, row.len(), "ragged matrix");
+            let mut row = row.clone();
+            row.push(rhs.clone());
+            row
+        })
+        .collect();
+
+    let pivots = row_reduce(&mut augmented);
+    // a pivot in the augmented column means the equations are inconsistent
+    if pivots.len() != unknowns || pivots.last() == Some(&unknowns) {
+        return None;
+    }
+    debug_assert!(augmented[..unknowns]
+        .iter()
+        .enumerate()
+        .all(|(i, row)| row[i].is_one()));
+    Some(
+        augmented
+            .into_iter()
+            .take(unknowns)
+            .map(|mut row| row.pop().unwrap())
+            .collect(),
+    )
+}
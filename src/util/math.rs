@@ -0,0 +1,103 @@
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    loop {
+        if b == 0 {
+            return a;
+        }
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+}
+
+/**
+Panics if the result overflows
+*/
+pub fn lcm(a: u64, b: u64) -> u64 {
+    checked_lcm(a, b).unwrap_or_else(|| panic!("lcm({a}, {b}) overflows"))
+}
+
+pub fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/**
+Returns `(g, x, y)` such that `a * x + b * y == g`, where `g` is the gcd of `a` and `b`
+*/
+#[allow(clippy::many_single_char_names)]
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/**
+The `x` in `0..m` such that `a * x` is congruent to 1 mod `m`, if there is one
+*/
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/**
+Chinese remainder theorem for moduli that need not be coprime. Each `(r, m)` is the congruence
+`x = r (mod m)`. Returns `(x, lcm of moduli)` with `x` in `0..lcm`, or `None` if the congruences
+conflict or the combined modulus overflows.
+*/
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut res: (i128, i128) = (0, 1);
+    for &(r2, m2) in congruences {
+        let (r1, m1) = res;
+        let (r2, m2) = (i128::from(r2).rem_euclid(i128::from(m2)), i128::from(m2));
+        // r1 + m1 * k = r2 (mod m2)  =>  m1 * k = r2 - r1 (mod m2)
+        let (g, p, _) = extended_gcd(m1, m2);
+        if (r2 - r1) % g != 0 {
+            return None;
+        }
+        let m2g = m2 / g;
+        let k = ((r2 - r1) / g % m2g * (p % m2g)).rem_euclid(m2g);
+        let m = m1.checked_mul(m2g)?;
+        res = ((r1 + m1 * k).rem_euclid(m), m);
+    }
+    Some((res.0.try_into().ok()?, res.1.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lcm_overflow() {
+        let big = 1 << 40;
+        assert_eq!(Some(big * 3), checked_lcm(big, big * 3));
+        assert_eq!(None, checked_lcm(big - 1, big + 1));
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(None, mod_inverse(4, 10));
+    }
+
+    #[test]
+    fn crt_non_coprime() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((10, 12)), crt(&[(4, 6), (2, 4)]));
+        assert_eq!(None, crt(&[(1, 6), (2, 4)]));
+        assert_eq!(Some((0, 1)), crt(&[]));
+    }
+}
@@ -1,15 +1,45 @@
 #![allow(dead_code)]
 mod counter;
+mod cycle;
 mod dir;
+mod dot;
+mod geometry;
+mod graph;
 mod grid;
+mod intern;
+mod interval;
+mod linalg;
+mod math;
+mod nbox;
+mod nonogram;
+mod parse;
 mod point;
 mod search;
+mod seq;
+mod symmetry;
+mod vcd;
 
+pub use aoc2023_derive::Parse;
 pub use counter::*;
+pub use cycle::*;
 pub use dir::*;
+pub use dot::*;
+pub use geometry::*;
+pub use graph::*;
 pub use grid::*;
+pub use intern::*;
+pub use interval::*;
+pub use linalg::*;
+pub use math::*;
+pub use nbox::*;
+pub use nonogram::*;
+pub use parse::*;
 pub use point::*;
 pub use search::*;
+pub use seq::*;
+pub use symmetry::*;
+pub use vcd::*;
+
 use std::fmt::Debug;
 use std::str::FromStr;
 
@@ -31,21 +61,6 @@ pub fn rev_chars(s: &str) -> String {
     s.chars().rev().collect()
 }
 
-pub fn gcd(mut a: u64, mut b: u64) -> u64 {
-    loop {
-        if b == 0 {
-            return a;
-        }
-        let t = a % b;
-        a = b;
-        b = t;
-    }
-}
-
-pub fn lcm(a: u64, b: u64) -> u64 {
-    (a * b) / gcd(a, b)
-}
-
 fn circ_mod(a: i32, b: i32) -> i32 {
     let r = a % b;
     if r < 0 {
@@ -0,0 +1,153 @@
+use std::ops::Range;
+
+/**
+An axis-aligned box in `D` dimensions, covering `min[i]..max[i]` on each axis. A box with nothing in
+it on some axis is empty.
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NBox<const D: usize> {
+    pub min: [i64; D],
+    pub max: [i64; D],
+}
+
+impl<const D: usize> NBox<D> {
+    pub fn new(min: [i64; D], max: [i64; D]) -> NBox<D> {
+        NBox { min, max }
+    }
+
+    /**
+    The smallest box containing both corners, which can be given in any order
+    */
+    pub fn from_corners(a: [i64; D], b: [i64; D]) -> NBox<D> {
+        NBox {
+            min: std::array::from_fn(|i| a[i].min(b[i])),
+            max: std::array::from_fn(|i| a[i].max(b[i]) + 1),
+        }
+    }
+
+    pub fn range(&self, axis: usize) -> Range<i64> {
+        self.min[axis]..self.max[axis]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..D).any(|i| self.min[i] >= self.max[i])
+    }
+
+    pub fn volume(&self) -> i64 {
+        if self.is_empty() {
+            0
+        } else {
+            (0..D).map(|i| self.max[i] - self.min[i]).product()
+        }
+    }
+
+    pub fn contains_point(&self, p: &[i64; D]) -> bool {
+        (0..D).all(|i| self.range(i).contains(&p[i]))
+    }
+
+    /**
+    Whether every point of `other` is in this box. Always true if `other` is empty.
+    */
+    pub fn contains(&self, other: &NBox<D>) -> bool {
+        other.is_empty()
+            || (0..D).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    pub fn overlaps(&self, other: &NBox<D>) -> bool {
+        (0..D).all(|i| self.min[i].max(other.min[i]) < self.max[i].min(other.max[i]))
+    }
+
+    pub fn intersection(&self, other: &NBox<D>) -> Option<NBox<D>> {
+        let res = NBox {
+            min: std::array::from_fn(|i| self.min[i].max(other.min[i])),
+            max: std::array::from_fn(|i| self.max[i].min(other.max[i])),
+        };
+        (!res.is_empty()).then_some(res)
+    }
+
+    /**
+    Splits into the part below `at` on `axis` and the part at or above it. Either part may be empty.
+    */
+    pub fn split_at(&self, axis: usize, at: i64) -> (NBox<D>, NBox<D>) {
+        let at = at.clamp(self.min[axis], self.max[axis].max(self.min[axis]));
+        let mut below = *self;
+        let mut above = *self;
+        below.max[axis] = at;
+        above.min[axis] = at;
+        (below, above)
+    }
+
+    /**
+    The part of this box outside `other`, as at most `2 * D` disjoint boxes
+    */
+    pub fn subtract(&self, other: &NBox<D>) -> Vec<NBox<D>> {
+        if !self.overlaps(other) {
+            return if self.is_empty() {
+                Vec::new()
+            } else {
+                vec![*self]
+            };
+        }
+        let mut res = Vec::new();
+        let mut rest = *self;
+        for axis in 0..D {
+            let (below, middle) = rest.split_at(axis, other.min[axis]);
+            let (middle, above) = middle.split_at(axis, other.max[axis]);
+            res.extend([below, above].into_iter().filter(|b| !b.is_empty()));
+            rest = middle;
+        }
+        res
+    }
+
+    pub fn translate(&self, offset: [i64; D]) -> NBox<D> {
+        NBox {
+            min: std::array::from_fn(|i| self.min[i] + offset[i]),
+            max: std::array::from_fn(|i| self.max[i] + offset[i]),
+        }
+    }
+
+    /**
+    The box in the first `E` dimensions, such as the footprint of a 3D box on the xy plane
+    */
+    pub fn project<const E: usize>(&self) -> NBox<E> {
+        assert!(E <= D, "cannot project {D} dimensions into {E}");
+        NBox {
+            min: std::array::from_fn(|i| self.min[i]),
+            max: std::array::from_fn(|i| self.max[i]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn box_ops() {
+        let a = NBox::from_corners([0, 0, 0], [3, 3, 3]);
+        let b = NBox::new([2, 2, 2], [6, 6, 6]);
+        assert_eq!(64, a.volume());
+        assert!(a.overlaps(&b));
+        assert_eq!(Some(NBox::new([2, 2, 2], [4, 4, 4])), a.intersection(&b));
+        assert!(!a.overlaps(&b.translate([2, 0, 0])));
+        assert!(a.contains(&NBox::new([1, 1, 1], [4, 2, 2])));
+        assert!(!a.contains(&b));
+        assert!(a.contains_point(&[3, 0, 1]));
+        assert!(!a.contains_point(&[4, 0, 1]));
+
+        let parts = a.subtract(&b);
+        assert_eq!(64 - 8, parts.iter().map(NBox::volume).sum::<i64>());
+        for (i, p) in parts.iter().enumerate() {
+            assert!(a.contains(p));
+            assert!(!p.overlaps(&b));
+            assert!(parts[i + 1..].iter().all(|q| !p.overlaps(q)));
+        }
+        assert_eq!(vec![a], a.subtract(&b.translate([5, 0, 0])));
+        assert!(b.subtract(&NBox::new([0, 0, 0], [10, 10, 10])).is_empty());
+
+        let (below, above) = a.split_at(2, 10);
+        assert_eq!(a, below);
+        assert!(above.is_empty());
+        assert_eq!(NBox::new([0, 0], [4, 4]), a.project::<2>());
+    }
+}
@@ -0,0 +1,361 @@
+use crate::util::{ints, sections, Grid, ParseError, Point};
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Cell {
+    Empty,
+    Filled,
+    Unknown,
+}
+
+impl From<char> for Cell {
+    fn from(c: char) -> Self {
+        match c {
+            '.' => Cell::Empty,
+            '#' => Cell::Filled,
+            '?' => Cell::Unknown,
+            _ => panic!("cell {c}"),
+        }
+    }
+}
+
+impl Debug for Cell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Cell::Empty => '.',
+            Cell::Filled => '#',
+            Cell::Unknown => '?',
+        };
+        write!(f, "{c}")
+    }
+}
+
+/**
+The number of ways to fill in the unknown cells of a line so that its runs of filled cells have the
+lengths in `runs`, in order. Saturates rather than overflowing.
+*/
+pub fn arrangements(cells: &[Cell], runs: &[usize]) -> u64 {
+    Arrangements::new(cells, runs).count()
+}
+
+/**
+The arrangements of a line, ordered by the first cell where they differ with empty before filled.
+They can be counted, listed, and picked out by index without generating the ones before. Indexing
+is only exact while the count fits in a `u64`.
+*/
+pub struct Arrangements<'a> {
+    cells: &'a [Cell],
+    runs: &'a [usize],
+    /**
+    `ways[i][r]` is the number of arrangements of `cells[i..]` with `runs[r..]`
+    */
+    ways: Vec<Vec<u64>>,
+}
+
+impl<'a> Arrangements<'a> {
+    pub fn new(cells: &'a [Cell], runs: &'a [usize]) -> Arrangements<'a> {
+        let mut res = Arrangements {
+            cells,
+            runs,
+            ways: vec![vec![0; runs.len() + 1]; cells.len() + 1],
+        };
+        res.ways[cells.len()][runs.len()] = 1;
+        for i in (0..cells.len()).rev() {
+            for r in 0..=runs.len() {
+                let empty = res.empty_ways(i, r);
+                let filled = res.run_at(i, r).map_or(0, |next| res.ways[next][r + 1]);
+                res.ways[i][r] = empty.saturating_add(filled);
+            }
+        }
+        res
+    }
+
+    pub fn count(&self) -> u64 {
+        self.ways[0][0]
+    }
+
+    /**
+    The `k`th arrangement, from 0
+    */
+    pub fn nth(&self, mut k: u64) -> Option<Vec<Cell>> {
+        if k >= self.count() {
+            return None;
+        }
+        let mut res = Vec::with_capacity(self.cells.len());
+        let (mut i, mut r) = (0, 0);
+        while i < self.cells.len() {
+            let empty = self.empty_ways(i, r);
+            if k < empty {
+                res.push(Cell::Empty);
+                i += 1;
+            } else {
+                k -= empty;
+                let next = self.run_at(i, r).expect("no arrangement left");
+                res.extend(std::iter::repeat_n(Cell::Filled, self.runs[r]));
+                res.resize(next, Cell::Empty);
+                i = next;
+                r += 1;
+            }
+        }
+        Some(res)
+    }
+
+    /**
+    Every arrangement, in order, each one built as it is needed
+    */
+    pub fn iter(&self) -> impl Iterator<Item = Vec<Cell>> + '_ {
+        (0..self.count()).map_while(|k| self.nth(k))
+    }
+
+    /**
+    An arrangement picked at random, where `below(n)` picks an index from `0..n`. The pick is as
+    uniform as `below` is.
+    */
+    pub fn sample(&self, mut below: impl FnMut(u64) -> u64) -> Option<Vec<Cell>> {
+        if self.count() == 0 {
+            return None;
+        }
+        self.nth(below(self.count()))
+    }
+
+    /**
+    The number of arrangements where `cells[i]` is empty
+    */
+    fn empty_ways(&self, i: usize, r: usize) -> u64 {
+        if self.cells[i] == Cell::Filled {
+            0
+        } else {
+            self.ways[i + 1][r]
+        }
+    }
+
+    /**
+    If run `r` can start at `i`, where the rest of the line starts after it and the empty cell that
+    has to follow it
+    */
+    fn run_at(&self, i: usize, r: usize) -> Option<usize> {
+        let len = *self.runs.get(r)?;
+        let end = i + len;
+        let fits = end <= self.cells.len()
+            && !self.cells[i..end].contains(&Cell::Empty)
+            && self.cells.get(end) != Some(&Cell::Filled);
+        fits.then(|| (end + 1).min(self.cells.len()))
+    }
+}
+
+/**
+Fills in every unknown cell of a line that has the same value in all of its arrangements, or `None`
+if it has no arrangements
+*/
+pub fn solve_line(cells: &[Cell], runs: &[usize]) -> Option<Vec<Cell>> {
+    if arrangements(cells, runs) == 0 {
+        return None;
+    }
+    let mut res = cells.to_vec();
+    for i in 0..cells.len() {
+        if cells[i] != Cell::Unknown {
+            continue;
+        }
+        res[i] = Cell::Filled;
+        let can_fill = arrangements(&res, runs) > 0;
+        res[i] = Cell::Empty;
+        let can_empty = arrangements(&res, runs) > 0;
+        res[i] = match (can_fill, can_empty) {
+            (true, false) => Cell::Filled,
+            (false, true) => Cell::Empty,
+            _ => Cell::Unknown,
+        };
+    }
+    Some(res)
+}
+
+/**
+A picture puzzle where each row and column is clued with the lengths of its runs of filled cells
+*/
+pub struct Nonogram {
+    pub rows: Vec<Vec<usize>>,
+    pub columns: Vec<Vec<usize>>,
+}
+
+/**
+How many ways a nonogram can be filled in, counting no further than the limit given to
+[`Nonogram::solve`], and the first one found
+*/
+#[derive(Debug)]
+pub struct Solutions {
+    pub count: u64,
+    pub first: Option<Grid<Cell>>,
+}
+
+impl Solutions {
+    pub fn is_unique(&self) -> bool {
+        self.count == 1
+    }
+}
+
+impl Nonogram {
+    /**
+    Solves by filling in whatever the clues of each row and column force, and guessing a cell when
+    that gets stuck. Stops once `limit` solutions are found, so a limit of 2 is enough to tell a
+    unique solution from an ambiguous one.
+    */
+    pub fn solve(&self, limit: u64) -> Solutions {
+        let width = i32::try_from(self.columns.len()).unwrap();
+        let height = i32::try_from(self.rows.len()).unwrap();
+        let grid = Grid::build(width, height, |_| Cell::Unknown);
+        let mut res = Solutions {
+            count: 0,
+            first: None,
+        };
+        self.search(grid, limit, &mut res);
+        res
+    }
+
+    fn search(&self, grid: Grid<Cell>, limit: u64, res: &mut Solutions) {
+        let Some(grid) = self.propagate(grid) else {
+            return;
+        };
+        let Some(guess) = grid.points().find(|p| grid[*p] == Cell::Unknown) else {
+            res.count += 1;
+            res.first.get_or_insert(grid);
+            return;
+        };
+        log::debug!("guessing at {guess}");
+        for cell in [Cell::Filled, Cell::Empty] {
+            if res.count >= limit {
+                return;
+            }
+            let mut next = grid.clone();
+            next[guess] = cell;
+            self.search(next, limit, res);
+        }
+    }
+
+    /**
+    Solves lines until none of them change, or `None` if some line can't be solved
+    */
+    fn propagate(&self, mut grid: Grid<Cell>) -> Option<Grid<Cell>> {
+        let lines = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(y, runs)| {
+                let y = i32::try_from(y).unwrap();
+                ((0..grid.width()).map(|x| Point(x, y)).collect(), runs)
+            })
+            .chain(self.columns.iter().enumerate().map(|(x, runs)| {
+                let x = i32::try_from(x).unwrap();
+                ((0..grid.height()).map(|y| Point(x, y)).collect(), runs)
+            }))
+            .collect::<Vec<(Vec<Point>, _)>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (points, runs) in &lines {
+                let cells: Vec<_> = points.iter().map(|p| grid[*p]).collect();
+                if !cells.contains(&Cell::Unknown) {
+                    if arrangements(&cells, runs) == 0 {
+                        return None;
+                    }
+                    continue;
+                }
+                let solved = solve_line(&cells, runs)?;
+                for (p, (old, new)) in points.iter().zip(cells.iter().zip(solved)) {
+                    if *old != new {
+                        grid[*p] = new;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        Some(grid)
+    }
+}
+
+/**
+Parses the row clues, a blank line, then the column clues, one line each. The lengths in a clue are
+separated by anything that isn't a digit, and `0` is a line with nothing filled.
+*/
+impl FromStr for Nonogram {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_clues = |section: &str| -> Result<Vec<Vec<usize>>, ParseError> {
+            section
+                .lines()
+                .enumerate()
+                .map(|(i, line)| {
+                    let runs: Vec<usize> = ints(line).map_err(|e| e.on_line(i + 1))?;
+                    Ok(runs.into_iter().filter(|r| *r > 0).collect())
+                })
+                .collect()
+        };
+        let mut sections = sections(s);
+        let (Some(rows), Some(columns)) = (sections.next(), sections.next()) else {
+            return Err(ParseError::at(0, "expected rows and columns"));
+        };
+        Ok(Nonogram {
+            rows: parse_clues(rows)?,
+            columns: parse_clues(columns)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(s: &str) -> Vec<Cell> {
+        s.chars().map(Cell::from).collect()
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(10, arrangements(&line("?###????????"), &[3, 2, 1]));
+        assert_eq!(
+            Some(line("??######??")),
+            solve_line(&line("??????????"), &[8])
+        );
+        assert_eq!(Some(line("#.#.#")), solve_line(&line("?????"), &[1, 1, 1]));
+        assert_eq!(Some(line(".###.")), solve_line(&line("?#?#?"), &[3]));
+        assert_eq!(None, solve_line(&line("#?#?#"), &[3]));
+    }
+
+    #[test]
+    fn enumerate() {
+        let cells = line("??.??#");
+        let all_ways = Arrangements::new(&cells, &[1, 2]);
+        assert_eq!(arrangements(&cells, &[1, 2]), all_ways.count());
+        let all: Vec<_> = all_ways.iter().collect();
+        assert_eq!(vec![line(".#..##"), line("#...##")], all);
+        assert_eq!(None, all_ways.nth(2));
+
+        assert_eq!(Some(all[0].clone()), all_ways.sample(|_| 0));
+        assert_eq!(Some(all[1].clone()), all_ways.sample(|n| n - 1));
+        let cells = line("#?");
+        assert_eq!(None, Arrangements::new(&cells, &[2, 1]).sample(|_| 0));
+    }
+
+    #[test]
+    fn puzzles() {
+        let plus: Nonogram = "1\n3\n1\n\n1\n3\n1".parse().unwrap();
+        let solutions = plus.solve(2);
+        assert!(solutions.is_unique());
+        assert_eq!(".#.\n###\n.#.\n", format!("{:?}", solutions.first.unwrap()));
+
+        // either diagonal, which takes a guess to find
+        let diagonal: Nonogram = "1\n1\n\n1\n1".parse().unwrap();
+        assert_eq!(2, diagonal.solve(10).count);
+        assert_eq!(1, diagonal.solve(1).count);
+
+        let impossible: Nonogram = "2\n0\n\n1\n0".parse().unwrap();
+        assert_eq!(0, impossible.solve(2).count);
+
+        let empty_rows: Nonogram = "0\n1 1\n\n1\n0\n1".parse().unwrap();
+        assert_eq!(
+            "...\n#.#\n",
+            format!("{:?}", empty_rows.solve(2).first.unwrap())
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/**
+A parse failure, with the 1-based column in the text being parsed and, if known, the 1-based line
+and the name of the field being parsed
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub column: usize,
+    pub field: Option<&'static str>,
+    pub message: String,
+}
+
+impl ParseError {
+    /**
+    An error at byte `offset` of the text being parsed
+    */
+    pub fn at(offset: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: None,
+            column: offset + 1,
+            field: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn on_line(mut self, line: usize) -> ParseError {
+        self.line = Some(line);
+        self
+    }
+
+    /**
+    Names the field that failed, unless a more deeply nested one already has been
+    */
+    pub fn for_field(mut self, field: &'static str) -> ParseError {
+        self.field.get_or_insert(field);
+        self
+    }
+
+    /**
+    Moves the error `offset` bytes to the right, for an error found in a slice of a larger text
+    */
+    fn shifted(mut self, offset: usize) -> ParseError {
+        self.column += offset;
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}, ")?;
+        }
+        write!(f, "column {}: ", self.column)?;
+        if let Some(field) = self.field {
+            write!(f, "{field}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/**
+Parses each line with `parser`, stamping any error with the line it happened on
+*/
+pub fn try_parse_lines<T>(
+    input: &str,
+    mut parser: impl FnMut(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parser(line).map_err(|e| e.on_line(i + 1)))
+        .collect()
+}
+
+/**
+Parses `s`, trimmed of surrounding whitespace, reporting failure at `offset`
+*/
+pub fn parse_at<T: FromStr>(s: &str, offset: usize) -> Result<T, ParseError>
+where
+    T::Err: Display,
+{
+    let trimmed = s.trim();
+    let leading = s.len() - s.trim_start().len();
+    trimmed.parse().map_err(|e| {
+        ParseError::at(
+            offset + leading,
+            format!(
+                "{trimmed:?} is not a valid {}: {e}",
+                std::any::type_name::<T>()
+            ),
+        )
+    })
+}
+
+/**
+Every integer in `s`, in order. A `-` directly before a number makes it negative; anything else that
+isn't a digit is skipped.
+*/
+pub fn ints<T: FromStr>(s: &str) -> Result<Vec<T>, ParseError>
+where
+    T::Err: Display,
+{
+    let bytes = s.as_bytes();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if !negative && !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        res.push(parse_at(&s[start..i], start)?);
+    }
+    Ok(res)
+}
+
+/**
+The blank-line-separated sections of `input`, without their surrounding newlines
+*/
+pub fn sections(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split("\n\n")
+        .map(|section| section.trim_matches('\n'))
+        .filter(|section| !section.is_empty())
+}
+
+/**
+Splits a list like `a=1,b=2` into trimmed `(key, value)` pairs, where `item_sep` separates the items
+and `kv_sep` separates each key from its value
+*/
+pub fn key_values<'a>(
+    s: &'a str,
+    item_sep: &str,
+    kv_sep: &str,
+) -> Result<Vec<(&'a str, &'a str)>, ParseError> {
+    let mut res = Vec::new();
+    let mut offset = 0;
+    for item in s.split(item_sep) {
+        let leading = item.len() - item.trim_start().len();
+        let (k, v) = item.trim().split_once(kv_sep).ok_or_else(|| {
+            ParseError::at(offset + leading, format!("expected {kv_sep:?} in {item:?}"))
+        })?;
+        res.push((k.trim(), v.trim()));
+        offset += item.len() + item_sep.len();
+    }
+    Ok(res)
+}
+
+/**
+Matches `s` against `pattern`, where each `{}` in the pattern captures the text up to the literal
+text that follows it (or the rest of `s`, for a trailing `{}`). Returns each capture with its offset.
+*/
+pub fn pattern<'a>(pattern: &str, s: &'a str) -> Result<Vec<(usize, &'a str)>, ParseError> {
+    let literals: Vec<_> = pattern.split("{}").collect();
+    match_literals(&literals, s)
+}
+
+/**
+Matches `s` against literal text with a capture between each consecutive pair of literals, as
+`pattern` does for the pieces of its pattern around each `{}`
+*/
+pub fn match_literals<'a>(
+    literals: &[&str],
+    s: &'a str,
+) -> Result<Vec<(usize, &'a str)>, ParseError> {
+    let (first, literals) = literals.split_first().expect("no literals");
+    if !s.starts_with(first) {
+        return Err(ParseError::at(0, format!("expected {first:?}")));
+    }
+    let mut pos = first.len();
+    let mut captures = Vec::new();
+    for literal in literals {
+        let end = if literal.is_empty() {
+            s.len()
+        } else {
+            s[pos..]
+                .find(literal)
+                .map(|i| pos + i)
+                .ok_or_else(|| ParseError::at(pos, format!("expected {literal:?}")))?
+        };
+        captures.push((pos, &s[pos..end]));
+        pos = end + literal.len();
+    }
+    if pos != s.len() {
+        return Err(ParseError::at(pos, "unexpected trailing text"));
+    }
+    Ok(captures)
+}
+
+/**
+Matches `s` against `pattern` like `pattern`, then parses the captures into a tuple
+*/
+pub fn parse_pattern<T: FromFields>(pat: &str, s: &str) -> Result<T, ParseError> {
+    let fields = pattern(pat, s)?;
+    if fields.len() != T::COUNT {
+        return Err(ParseError::at(
+            0,
+            format!("{pat:?} has {} fields, expected {}", fields.len(), T::COUNT),
+        ));
+    }
+    T::from_fields(&fields)
+}
+
+/**
+Tuples that can be built from the captures of a pattern, one field per capture
+*/
+pub trait FromFields: Sized {
+    const COUNT: usize;
+
+    fn from_fields(fields: &[(usize, &str)]) -> Result<Self, ParseError>;
+}
+
+macro_rules! from_fields_tuple {
+    ($count:literal; $($t:ident $i:tt),+) => {
+        impl<$($t: FromStr),+> FromFields for ($($t,)+)
+        where
+            $($t::Err: Display),+
+        {
+            const COUNT: usize = $count;
+
+            fn from_fields(fields: &[(usize, &str)]) -> Result<Self, ParseError> {
+                Ok(($(parse_at::<$t>(fields[$i].1, fields[$i].0)?,)+))
+            }
+        }
+    };
+}
+
+from_fields_tuple!(1; A 0);
+from_fields_tuple!(2; A 0, B 1);
+from_fields_tuple!(3; A 0, B 1, C 2);
+from_fields_tuple!(4; A 0, B 1, C 2, D 3);
+from_fields_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+
+/**
+Where `inner`, which must be a slice of `outer`, starts in `outer`
+*/
+pub fn offset_of(outer: &str, inner: &str) -> usize {
+    let offset = (inner.as_ptr() as usize)
+        .checked_sub(outer.as_ptr() as usize)
+        .filter(|o| o + inner.len() <= outer.len());
+    offset.expect("not a slice of the outer text")
+}
+
+/**
+Parses a field of a larger text that starts at `offset`, so errors point into the larger text
+*/
+pub fn parse_field<T>(
+    offset: usize,
+    parser: impl FnOnce() -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    parser().map_err(|e| e.shifted(offset))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::Parse;
+
+    #[derive(Parse, Debug, PartialEq)]
+    #[parse("{{{name}}} = {x},{y} [{tags}]")]
+    struct Record {
+        name: String,
+        x: i32,
+        y: u8,
+        #[parse(with = ints)]
+        tags: Vec<i64>,
+    }
+
+    #[derive(Parse, Debug, PartialEq)]
+    #[parse("{name} -> {count}x {item}")]
+    struct Borrowed<'a> {
+        name: &'a str,
+        count: u32,
+        item: &'a str,
+    }
+
+    #[test]
+    fn extract_ints() {
+        assert_eq!(
+            vec![19, 13, 30, -2, 1, -2],
+            ints::<i64>("19, 13, 30 @ -2,  1, -2").unwrap()
+        );
+        assert_eq!(vec![1, 2, -3], ints::<i32>("a1-b2c--3").unwrap());
+        let err = ints::<u8>("1 2 300").unwrap_err();
+        assert_eq!(5, err.column);
+    }
+
+    #[test]
+    fn split_sections() {
+        let input = "a\nb\n\nc\n\n\nd\n";
+        assert_eq!(vec!["a\nb", "c", "d"], sections(input).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn pairs() {
+        assert_eq!(
+            vec![("x", "787"), ("m", "2655")],
+            key_values("x=787,m=2655", ",", "=").unwrap()
+        );
+        let err = key_values("x=1,m2", ",", "=").unwrap_err();
+        assert_eq!(5, err.column);
+    }
+
+    #[test]
+    fn patterns() {
+        let (name, left, right): (String, String, String) =
+            parse_pattern("{} = ({}, {})", "AAA = (BBB, CCC)").unwrap();
+        assert_eq!(("AAA", "BBB", "CCC"), (&*name, &*left, &*right));
+
+        let (id, rest): (u32, String) = parse_pattern("Game {}: {}", "Game 12: 3 blue").unwrap();
+        assert_eq!((12, "3 blue"), (id, &*rest));
+
+        let err = parse_pattern::<(u32, String)>("Game {}: {}", "Game x: 3 blue").unwrap_err();
+        assert_eq!(6, err.column);
+        let err = parse_pattern::<(u32, String)>("Game {}: {}", "Game 1 3 blue").unwrap_err();
+        assert_eq!(6, err.column);
+
+        let err = try_parse_lines("Game 1: a\nGame b: c", |line| {
+            parse_pattern::<(u32, String)>("Game {}: {}", line)
+        })
+        .unwrap_err();
+        assert_eq!(
+            "line 2, column 6: \"b\" is not a valid u32: invalid digit found in string",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn derived() {
+        let record: Record = "{abc} = -3,7 [1 2 -3]".parse().unwrap();
+        assert_eq!(
+            Record {
+                name: "abc".to_string(),
+                x: -3,
+                y: 7,
+                tags: vec![1, 2, -3],
+            },
+            record
+        );
+
+        let err = "{abc} = -3,700 [1]".parse::<Record>().unwrap_err();
+        assert_eq!((Some("y"), 12), (err.field, err.column));
+        let err = "{abc} = -3,7 [1 99999999999999999999]"
+            .parse::<Record>()
+            .unwrap_err();
+        assert_eq!((Some("tags"), 17), (err.field, err.column));
+        let err = "{abc} -3,7 [1]".parse::<Record>().unwrap_err();
+        assert_eq!((None, 2), (err.field, err.column));
+    }
+
+    #[test]
+    fn derived_borrowed() {
+        let line = String::from("abc ->  3x widget");
+        let parsed = Borrowed::parse(&line).unwrap();
+        assert_eq!(
+            Borrowed {
+                name: "abc",
+                count: 3,
+                item: "widget",
+            },
+            parsed
+        );
+        let err = Borrowed::parse("abc -> ax widget").unwrap_err();
+        assert_eq!((Some("count"), 8), (err.field, err.column));
+    }
+}
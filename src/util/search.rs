@@ -1,7 +1,9 @@
 use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
+use std::ops::Add;
 
 /**
 Represents a searchable world
@@ -51,6 +53,29 @@ impl<S: Searchable> PartialEq for KeyWithItem<S> {
 impl<S: Searchable> Eq for KeyWithItem<S> {}
 
 pub fn search<S: Searchable>(search: &S) -> Option<(S::State, S::Value)> {
+    best_first(search, usize::MAX).unwrap_or_else(|BudgetExceeded| unreachable!())
+}
+
+/**
+Like `search`, but once the number of seen keys plus queued states exceeds `max_entries`, the
+best-first search is abandoned and `ida_star` is used instead, which only keeps the current path in
+memory.
+*/
+pub fn search_with_budget<S: Searchable>(
+    search: &S,
+    max_entries: usize,
+) -> Option<(S::State, S::Value)> {
+    best_first(search, max_entries).unwrap_or_else(|BudgetExceeded| {
+        log::info!("search exceeded {max_entries} entries, falling back to IDA*");
+        ida_star(search)
+    })
+}
+
+struct BudgetExceeded;
+
+type Found<S> = Option<(<S as Searchable>::State, <S as Searchable>::Value)>;
+
+fn best_first<S: Searchable>(search: &S, max_entries: usize) -> Result<Found<S>, BudgetExceeded> {
     let initial_state = search.initial_state();
     let break_on_goal = S::break_on_goal();
     let use_value_estimate = S::use_value_estimate();
@@ -107,6 +132,9 @@ pub fn search<S: Searchable>(search: &S) -> Option<(S::State, S::Value)> {
         }
 
         seen.insert(key, value);
+        if seen.len() + q.len() > max_entries {
+            return Err(BudgetExceeded);
+        }
 
         for succ in search.successors(s) {
             let succ_key = search.key(&succ);
@@ -128,9 +156,277 @@ pub fn search<S: Searchable>(search: &S) -> Option<(S::State, S::Value)> {
         }
     }
 
+    Ok(best)
+}
+
+fn estimate<S: Searchable>(search: &S, state: &S::State) -> S::Value {
+    if S::use_value_estimate() {
+        search.value_estimate(state)
+    } else {
+        search.value(state)
+    }
+}
+
+/**
+Iterative deepening A*. Runs repeated depth-first searches that prune any state whose estimate is
+below a bound, starting with the estimate of the initial state and lowering the bound to the best
+pruned estimate each round. Only the keys on the current path are kept in memory, so states reachable
+through several paths will be explored more than once.
+*/
+pub fn ida_star<S: Searchable>(search: &S) -> Option<(S::State, S::Value)> {
+    let mut bound = estimate(search, &search.initial_state());
+    loop {
+        log::debug!("IDA* bound {bound:?}");
+        let mut next_bound = None;
+        let mut path = Vec::new();
+        let res = ida_dfs(
+            search,
+            search.initial_state(),
+            &bound,
+            &mut path,
+            &mut next_bound,
+        );
+        if res.is_some() {
+            return res;
+        }
+        bound = next_bound?;
+    }
+}
+
+fn ida_dfs<S: Searchable>(
+    search: &S,
+    state: S::State,
+    bound: &S::Value,
+    path: &mut Vec<S::Key>,
+    next_bound: &mut Option<S::Value>,
+) -> Option<(S::State, S::Value)> {
+    let est = estimate(search, &state);
+    if &est < bound {
+        raise(next_bound, est);
+        return None;
+    }
+
+    if search.is_goal(&state) {
+        let value = search.value(&state);
+        if &value >= bound {
+            return Some((state, value));
+        }
+        raise(next_bound, value);
+        return None;
+    }
+
+    let key = search.key(&state);
+    if path.contains(&key) {
+        return None;
+    }
+    path.push(key);
+    let mut res = None;
+    for succ in search.successors(state) {
+        res = ida_dfs(search, succ, bound, path, next_bound);
+        if res.is_some() {
+            break;
+        }
+    }
+    path.pop();
+    res
+}
+
+fn raise<V: Ord>(current: &mut Option<V>, v: V) {
+    if current.as_ref().is_none_or(|c| &v > c) {
+        *current = Some(v);
+    }
+}
+
+/**
+Depth-first search that follows at most `max_depth` transitions from the initial state, and returns
+the highest value goal state found. Like `search`, states whose estimate cannot beat the best goal so
+far are pruned, and `break_on_goal` stops at the first goal found. Only the keys on the current path
+are kept in memory.
+*/
+pub fn depth_limited_search<S: Searchable>(
+    search: &S,
+    max_depth: usize,
+) -> Option<(S::State, S::Value)> {
+    let mut best = None;
+    let mut path = Vec::new();
+    dl_dfs(
+        search,
+        search.initial_state(),
+        max_depth,
+        &mut path,
+        &mut best,
+    );
     best
 }
 
+// returns true if the search should stop
+fn dl_dfs<S: Searchable>(
+    search: &S,
+    state: S::State,
+    depth: usize,
+    path: &mut Vec<S::Key>,
+    best: &mut Option<(S::State, S::Value)>,
+) -> bool {
+    if let Some((_, best_v)) = best.as_ref() {
+        if &estimate(search, &state) <= best_v {
+            return false;
+        }
+    }
+
+    if search.is_goal(&state) {
+        let value = search.value(&state);
+        if best.as_ref().is_none_or(|(_, best_v)| &value > best_v) {
+            *best = Some((state, value));
+        }
+        return S::break_on_goal();
+    }
+
+    if depth == 0 {
+        return false;
+    }
+
+    let key = search.key(&state);
+    if path.contains(&key) {
+        return false;
+    }
+    path.push(key);
+    let mut stop = false;
+    for succ in search.successors(state) {
+        if dl_dfs(search, succ, depth - 1, path, best) {
+            stop = true;
+            break;
+        }
+    }
+    path.pop();
+    stop
+}
+
+/**
+Bidirectional Dijkstra from `start` to `goal`. `forward` returns the successors of a key along with
+the cost of the edge to each, and `reverse` returns the predecessors of a key with the cost of the
+edge from each. Costs must be non-negative. Use a cost of 1 for every edge for a bidirectional BFS.
+
+Returns the path from `start` to `goal` (inclusive) and its total cost.
+*/
+pub fn bidirectional_search<K, C, F, R>(
+    start: K,
+    goal: K,
+    mut forward: F,
+    mut reverse: R,
+) -> Option<(Vec<K>, C)>
+where
+    K: Hash + Eq + Clone + Debug,
+    C: Ord + Copy + Default + Add<Output = C> + Debug,
+    F: FnMut(&K) -> Vec<(K, C)>,
+    R: FnMut(&K) -> Vec<(K, C)>,
+{
+    if start == goal {
+        return Some((vec![start], C::default()));
+    }
+
+    let mut fwd: Frontier<K, C> = Frontier::new(start);
+    let mut bwd: Frontier<K, C> = Frontier::new(goal);
+    // (total cost, meeting key)
+    let mut best: Option<(C, K)> = None;
+
+    while let (Some(f_top), Some(b_top)) = (fwd.peek(), bwd.peek()) {
+        if let Some((best_cost, _)) = &best {
+            if f_top + b_top >= *best_cost {
+                break;
+            }
+        }
+
+        // expand whichever side has the cheaper frontier
+        let is_forward = f_top <= b_top;
+        let (this, other) = if is_forward {
+            (&mut fwd, &bwd)
+        } else {
+            (&mut bwd, &fwd)
+        };
+        let Some((key, cost)) = this.pop() else {
+            break;
+        };
+        log::debug!("expanding {key:?}, cost {cost:?}, forward {is_forward}");
+        let edges = if is_forward {
+            forward(&key)
+        } else {
+            reverse(&key)
+        };
+        for (next, edge) in edges {
+            let next_cost = cost + edge;
+            if !this.relax(&key, next.clone(), next_cost) {
+                continue;
+            }
+            if let Some(other_cost) = other.dist.get(&next) {
+                let total = next_cost + *other_cost;
+                if best
+                    .as_ref()
+                    .is_none_or(|(best_cost, _)| total < *best_cost)
+                {
+                    best = Some((total, next));
+                }
+            }
+        }
+    }
+
+    let (cost, meet) = best?;
+    let mut path = fwd.path_to(&meet);
+    path.reverse();
+    path.pop();
+    path.extend(bwd.path_to(&meet));
+    Some((path, cost))
+}
+
+struct Frontier<K: Hash + Eq, C: Ord> {
+    q: PriorityQueue<K, Reverse<C>>,
+    dist: HashMap<K, C>,
+    parent: HashMap<K, K>,
+}
+
+impl<K: Hash + Eq + Clone, C: Ord + Copy + Default> Frontier<K, C> {
+    fn new(start: K) -> Self {
+        let mut q = PriorityQueue::new();
+        let mut dist = HashMap::new();
+        dist.insert(start.clone(), C::default());
+        q.push(start, Reverse(C::default()));
+        Frontier {
+            q,
+            dist,
+            parent: HashMap::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<C> {
+        self.q.peek().map(|(_, Reverse(c))| *c)
+    }
+
+    fn pop(&mut self) -> Option<(K, C)> {
+        self.q.pop().map(|(k, Reverse(c))| (k, c))
+    }
+
+    // returns true if next was improved
+    fn relax(&mut self, from: &K, next: K, cost: C) -> bool {
+        if self.dist.get(&next).is_some_and(|old| *old <= cost) {
+            return false;
+        }
+        self.dist.insert(next.clone(), cost);
+        self.parent.insert(next.clone(), from.clone());
+        self.q.push_increase(next, Reverse(cost));
+        true
+    }
+
+    // path from key back to the root of this frontier, starting with key
+    fn path_to(&self, key: &K) -> Vec<K> {
+        let mut res = vec![key.clone()];
+        let mut current = key;
+        while let Some(p) = self.parent.get(current) {
+            res.push(p.clone());
+            current = p;
+        }
+        res
+    }
+}
+
 /*
 If this is pathfinding, successor states have lower value than predecessors.
 
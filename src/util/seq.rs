@@ -0,0 +1,119 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
+
+/**
+The rows of the difference table of `seq`, starting with `seq` itself and ending with the first row
+that is all zeros (or is empty)
+*/
+pub fn differences(seq: &[i64]) -> Vec<Vec<i64>> {
+    let mut rows = vec![seq.to_vec()];
+    while rows.last().unwrap().iter().any(|v| *v != 0) {
+        let next = rows
+            .last()
+            .unwrap()
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .collect();
+        rows.push(next);
+    }
+    rows
+}
+
+/**
+The next value of `seq`, assuming it is a polynomial of degree less than its length
+*/
+pub fn extrapolate_next(seq: &[i64]) -> i64 {
+    differences(seq).iter().filter_map(|row| row.last()).sum()
+}
+
+/**
+The value before the start of `seq`, assuming it is a polynomial of degree less than its length
+*/
+pub fn extrapolate_prev(seq: &[i64]) -> i64 {
+    differences(seq)
+        .iter()
+        .rev()
+        .filter_map(|row| row.first())
+        .fold(0, |acc, first| first - acc)
+}
+
+/**
+The degree of the polynomial that generates `seq`: the number of times it must be differenced to
+reach a constant row. Returns `None` if there aren't enough values to tell, meaning no row of at
+least two values is constant.
+*/
+pub fn degree(seq: &[i64]) -> Option<usize> {
+    differences(seq)
+        .iter()
+        .position(|row| row.len() >= 2 && row.iter().all(|v| *v == row[0]))
+}
+
+/**
+Evaluates the polynomial through `(i, samples[i])` at `x`, using Newton's forward difference formula.
+Exact for any `x`, which may be far beyond the samples.
+*/
+pub fn newton_eval(samples: &[i64], x: &BigInt) -> BigInt {
+    let mut res = BigInt::zero();
+    // binomial(x, k)
+    let mut binom = BigInt::from(1);
+    for (k, row) in differences(samples).iter().enumerate() {
+        let Some(first) = row.first() else {
+            break;
+        };
+        res += &binom * first;
+        binom = binom * (x - k) / (k + 1);
+    }
+    res
+}
+
+/**
+Evaluates the polynomial through `points` at `x` using Lagrange interpolation. The `x` values of
+the points must be distinct, but need not be evenly spaced.
+*/
+pub fn lagrange_eval(points: &[(i64, i64)], x: i64) -> BigRational {
+    let x = BigInt::from(x);
+    let mut res = BigRational::zero();
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        let mut num = BigInt::from(*yi);
+        let mut den = BigInt::from(1);
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                num *= &x - xj;
+                den *= xi - xj;
+            }
+        }
+        res += BigRational::new(num, den);
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extrapolate() {
+        assert_eq!(18, extrapolate_next(&[0, 3, 6, 9, 12, 15]));
+        assert_eq!(68, extrapolate_next(&[10, 13, 16, 21, 30, 45]));
+        assert_eq!(5, extrapolate_prev(&[10, 13, 16, 21, 30, 45]));
+        assert_eq!(Some(1), degree(&[0, 3, 6, 9, 12, 15]));
+        assert_eq!(Some(3), degree(&[10, 13, 16, 21, 30, 45]));
+        assert_eq!(None, degree(&[1, 2, 4, 8]));
+    }
+
+    #[test]
+    fn interpolate() {
+        // 3x^2 - 2x + 7
+        let samples = [7, 8, 15, 28];
+        let x = BigInt::from(1_000_000_000_000i64);
+        let expected = BigInt::from(3) * &x * &x - BigInt::from(2) * &x + 7;
+        assert_eq!(expected, newton_eval(&samples, &x));
+
+        let points = [(-3, 40), (2, 15), (10, 287)];
+        assert_eq!(
+            BigRational::from_integer(BigInt::from(7 + 3 * 25 - 2 * 5)),
+            lagrange_eval(&points, 5)
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::util::{Grid, Point};
+
+/**
+A way a grid could map onto itself
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Symmetry {
+    /**
+    Reflection in the vertical line between columns `x - 1` and `x`. Columns with no partner on
+    the other side of the line are ignored.
+    */
+    Vertical(i32),
+    /**
+    Reflection in the horizontal line between rows `y - 1` and `y`, ignoring rows with no partner
+    */
+    Horizontal(i32),
+    /**
+    Reflection in the diagonal from the top left corner, for square grids
+    */
+    Diagonal,
+    /**
+    Reflection in the diagonal from the top right corner, for square grids
+    */
+    AntiDiagonal,
+    /**
+    A quarter turn clockwise, for square grids
+    */
+    QuarterTurn,
+    HalfTurn,
+}
+
+/**
+A symmetry and the pairs of cells that should match under it but don't
+*/
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SymmetryReport {
+    pub symmetry: Symmetry,
+    pub mismatches: Vec<(Point, Point)>,
+}
+
+impl SymmetryReport {
+    pub fn is_exact(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/**
+Every horizontal and vertical reflection line strictly inside the grid, vertical lines first, each
+in order
+*/
+pub fn reflections<T: PartialEq>(grid: &Grid<T>) -> Vec<SymmetryReport> {
+    let (w, h) = (grid.width(), grid.height());
+    let vertical =
+        (1..w).map(|x| report(grid, Symmetry::Vertical(x), |p| Point(2 * x - 1 - p.0, p.1)));
+    let horizontal = (1..h).map(|y| {
+        report(grid, Symmetry::Horizontal(y), |p| {
+            Point(p.0, 2 * y - 1 - p.1)
+        })
+    });
+    vertical.chain(horizontal).collect()
+}
+
+/**
+Every reflection line, then the diagonal reflections and quarter turn if the grid is square, then
+the half turn
+*/
+pub fn symmetries<T: PartialEq>(grid: &Grid<T>) -> Vec<SymmetryReport> {
+    let (w, h) = (grid.width(), grid.height());
+    let mut res = reflections(grid);
+    if w == h {
+        res.push(report(grid, Symmetry::Diagonal, |p| Point(p.1, p.0)));
+        res.push(report(grid, Symmetry::AntiDiagonal, |p| {
+            Point(w - 1 - p.1, h - 1 - p.0)
+        }));
+        res.push(report(grid, Symmetry::QuarterTurn, |p| {
+            Point(w - 1 - p.1, p.0)
+        }));
+    }
+    res.push(report(grid, Symmetry::HalfTurn, |p| {
+        Point(w - 1 - p.0, h - 1 - p.1)
+    }));
+    res
+}
+
+/**
+Compares each cell with its image, skipping images outside the grid. Each pair is listed once, from
+the cell that comes first in reading order, except for the quarter turn, which isn't its own
+inverse, so every cell is compared with the cell it turns onto.
+*/
+fn report<T: PartialEq>(
+    grid: &Grid<T>,
+    symmetry: Symmetry,
+    image: impl Fn(Point) -> Point,
+) -> SymmetryReport {
+    let reading_order = |p: Point| (p.1, p.0);
+    let mismatches = grid
+        .points()
+        .filter_map(|p| {
+            let q = image(p);
+            let listed = symmetry == Symmetry::QuarterTurn || reading_order(p) < reading_order(q);
+            let differs = grid.get(q).is_some_and(|t| *t != grid[p]);
+            (listed && differs).then_some((p, q))
+        })
+        .collect();
+    SymmetryReport {
+        symmetry,
+        mismatches,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reflection_lines() {
+        let grid: Grid<char> = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#."
+            .parse()
+            .unwrap();
+        let reports = reflections(&grid);
+        assert_eq!(8 + 6, reports.len());
+        let exact: Vec<_> = reports
+            .iter()
+            .filter(|r| r.is_exact())
+            .map(|r| r.symmetry)
+            .collect();
+        assert_eq!(vec![Symmetry::Vertical(5)], exact);
+        let smudged = reports
+            .iter()
+            .find(|r| r.symmetry == Symmetry::Horizontal(3))
+            .unwrap();
+        assert_eq!(vec![(Point(0, 0), Point(0, 5))], smudged.mismatches);
+    }
+
+    #[test]
+    fn square() {
+        let grid: Grid<char> = "##.\n#..\n...".parse().unwrap();
+        let reports = symmetries(&grid);
+        let find = |s| reports.iter().find(|r| r.symmetry == s).unwrap();
+        assert!(find(Symmetry::Diagonal).is_exact());
+        assert_eq!(
+            vec![
+                (Point(0, 0), Point(2, 2)),
+                (Point(1, 0), Point(2, 1)),
+                (Point(0, 1), Point(1, 2))
+            ],
+            find(Symmetry::AntiDiagonal).mismatches
+        );
+        assert_eq!(3, find(Symmetry::HalfTurn).mismatches.len());
+        // two filled cells turn onto empty ones, and two empty ones onto filled ones
+        assert_eq!(4, find(Symmetry::QuarterTurn).mismatches.len());
+
+        let cross: Grid<char> = ".#.\n###\n.#.".parse().unwrap();
+        let exact: Vec<_> = symmetries(&cross)
+            .into_iter()
+            .filter(SymmetryReport::is_exact)
+            .map(|r| r.symmetry)
+            .collect();
+        assert_eq!(
+            vec![
+                Symmetry::Diagonal,
+                Symmetry::AntiDiagonal,
+                Symmetry::QuarterTurn,
+                Symmetry::HalfTurn
+            ],
+            exact
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::{self, Write};
+
+/**
+Writes one-bit signals in Value Change Dump format, which waveform viewers like `GTKWave` can open.
+Every signal starts at 0, and `changes` holds `(time, signal, value)` in time order. Changes that
+don't change the value are skipped.
+*/
+pub fn write_vcd(
+    out: &mut impl Write,
+    scope: &str,
+    signals: &[&str],
+    changes: impl IntoIterator<Item = (u64, usize, bool)>,
+) -> io::Result<()> {
+    writeln!(out, "$timescale 1ns $end")?;
+    writeln!(out, "$scope module {scope} $end")?;
+    for (i, name) in signals.iter().enumerate() {
+        writeln!(out, "$var wire 1 {} {name} $end", vcd_id(i))?;
+    }
+    writeln!(out, "$upscope $end")?;
+    writeln!(out, "$enddefinitions $end")?;
+    writeln!(out, "#0")?;
+    writeln!(out, "$dumpvars")?;
+    for i in 0..signals.len() {
+        writeln!(out, "0{}", vcd_id(i))?;
+    }
+    writeln!(out, "$end")?;
+
+    let mut values = vec![false; signals.len()];
+    let mut last_time = 0;
+    for (time, signal, value) in changes {
+        if values[signal] == value {
+            continue;
+        }
+        values[signal] = value;
+        if time != last_time {
+            writeln!(out, "#{time}")?;
+            last_time = time;
+        }
+        writeln!(out, "{}{}", u8::from(value), vcd_id(signal))?;
+    }
+    Ok(())
+}
+
+/**
+The short identifier code for the `i`th signal, made of the printable characters `!` to `~`
+*/
+fn vcd_id(mut i: usize) -> String {
+    const BASE: usize = (b'~' - b'!' + 1) as usize;
+    let mut id = String::new();
+    loop {
+        id.push(char::from(b'!' + u8::try_from(i % BASE).unwrap()));
+        i /= BASE;
+        if i == 0 {
+            break;
+        }
+        i -= 1;
+    }
+    id
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dump() {
+        let mut out = Vec::new();
+        let changes = [(1, 0, true), (1, 1, true), (2, 1, true), (3, 0, false)];
+        write_vcd(&mut out, "top", &["a", "b"], changes).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("$timescale 1ns $end\n$scope module top $end\n"));
+        assert!(out.contains("$var wire 1 ! a $end\n$var wire 1 \" b $end\n"));
+        assert!(out.ends_with("$end\n#1\n1!\n1\"\n#3\n0!\n"));
+
+        assert_eq!("!", vcd_id(0));
+        assert_eq!("~", vcd_id(93));
+        assert_eq!("!!", vcd_id(94));
+        assert_eq!("\"!", vcd_id(95));
+        assert_eq!("!\"", vcd_id(94 * 2));
+    }
+}